@@ -1,142 +1,414 @@
-use std::iter::from_fn;
+use std::io::{self, ErrorKind, Read};
+use std::ops::Range;
 
-advent_of_code::solution!(3);
+use advent_of_code::template::generator::Rng;
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let tokens = tokenizer(input);
+advent_of_code::solution!(
+    3,
+    generate,
+    alternatives = [part_one_interpreter => 1, part_two_interpreter => 2]
+);
 
-    let mut total_result = 0;
-    let mut tok_cursor = 0;
+pub fn part_one(input: &str) -> Option<u64> {
+    scan(input.as_bytes(), &MulOnly).ok()
+}
+
+pub fn part_two(input: &str) -> Option<u64> {
+    scan(input.as_bytes(), &Conditional).ok()
+}
+
+/// Parses the whole program before running it, see `--variant interpreter --trace` for a highlighted trace.
+pub fn part_one_interpreter(input: &str) -> Option<u64> {
+    Some(interpret(input, &MulOnly))
+}
+
+pub fn part_two_interpreter(input: &str) -> Option<u64> {
+    Some(interpret(input, &Conditional))
+}
+
+/// Evaluates corrupted memory in a single pass over the bytes of `reader`, without allocating.
+/// Instructions may be split across reads.
+fn scan(mut reader: impl Read, instruction_set: &impl InstructionSet) -> io::Result<u64> {
+    let mut machine = Machine {
+        total: 0,
+        enabled: true,
+    };
+    let mut state = State::Start;
+    let mut buffer = [0; 8192];
 
-    while tok_cursor < tokens.len() {
-        match tokens[tok_cursor] {
-            Token::Mul => {
-                let (start, end) = (tok_cursor, (tok_cursor + 6));
-                if end > tokens.len() {
-                    break;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(machine.total),
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        let mut bytes = &buffer[..read];
+
+        while let Some((&byte, rest)) = bytes.split_first() {
+            bytes = rest;
+
+            state = match state.step(byte) {
+                Step::Next(State::Start) => {
+                    // skip junk up to the next byte that can start an instruction
+                    let skip = bytes
+                        .iter()
+                        .position(|&b| b == b'm' || b == b'd')
+                        .unwrap_or(bytes.len());
+                    bytes = &bytes[skip..];
+                    State::Start
                 }
-                if let Some((num1, num2)) = process_seq(&tokens[start..end]) {
-                    total_result += num1 * num2;
-                    tok_cursor += 5;
+                Step::Next(next) => next,
+                Step::Complete(instruction) => {
+                    instruction_set.execute(&mut machine, instruction);
+                    State::Start
                 }
-            }
-            Token::Eof => break,
-            _ => {}
+            };
         }
-        tok_cursor += 1
     }
-
-    Some(total_result)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let tokens = tokenizer(input);
+/// Progress of the scanner towards the next instruction, named after the bytes matched so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Start,
+    M,
+    Mu,
+    Mul,
+    First { value: u32, digits: u8 },
+    Second { first: u32, value: u32, digits: u8 },
+    D,
+    Do,
+    DoOpen,
+    Don,
+    DonQuote,
+    DonT,
+    DontOpen,
+}
 
-    let mut total_result = 0;
-    let mut tok_cursor = 0;
-    let mut mul_enabled = true;
+enum Step {
+    Next(State),
+    Complete(Instruction),
+}
 
-    while tok_cursor < tokens.len() {
-        match tokens[tok_cursor] {
-            Token::Mul => {
-                let (start, end) = (tok_cursor, (tok_cursor + 6));
-                if end > tokens.len() {
-                    break;
-                }
-                if let Some((num1, num2)) = process_seq(&tokens[start..end]) {
-                    if mul_enabled {
-                        total_result += num1 * num2;
-                    }
-                    tok_cursor += 5;
-                }
+impl State {
+    // NOTE: without inlining, returning `Step` through memory makes the scanner more than twice as slow.
+    #[inline(always)]
+    fn step(self, byte: u8) -> Step {
+        let next = match (self, byte) {
+            (Self::M, b'u') => Self::Mu,
+            (Self::Mu, b'l') => Self::Mul,
+            (Self::Mul, b'(') => Self::First {
+                value: 0,
+                digits: 0,
+            },
+            (Self::First { value, digits }, b'0'..=b'9') if digits < 3 => Self::First {
+                value: value * 10 + u32::from(byte - b'0'),
+                digits: digits + 1,
+            },
+            (Self::First { value, digits }, b',') if digits > 0 => Self::Second {
+                first: value,
+                value: 0,
+                digits: 0,
+            },
+            (
+                Self::Second {
+                    first,
+                    value,
+                    digits,
+                },
+                b'0'..=b'9',
+            ) if digits < 3 => Self::Second {
+                first,
+                value: value * 10 + u32::from(byte - b'0'),
+                digits: digits + 1,
+            },
+            (
+                Self::Second {
+                    first,
+                    value,
+                    digits,
+                },
+                b')',
+            ) if digits > 0 => {
+                return Step::Complete(Instruction::Mul(first, value));
             }
-            Token::Do => mul_enabled = true,
-            Token::Dont => mul_enabled = false,
-            Token::Eof => break,
-            _ => {}
-        }
-        tok_cursor += 1
+            (Self::D, b'o') => Self::Do,
+            (Self::Do, b'(') => Self::DoOpen,
+            (Self::DoOpen, b')') => return Step::Complete(Instruction::Do),
+            (Self::Do, b'n') => Self::Don,
+            (Self::Don, b'\'') => Self::DonQuote,
+            (Self::DonQuote, b't') => Self::DonT,
+            (Self::DonT, b'(') => Self::DontOpen,
+            (Self::DontOpen, b')') => return Step::Complete(Instruction::Dont),
+            // NOTE: no instruction can start inside a partial match other than at the byte that broke it,
+            // so there is never a need to backtrack.
+            (_, b'm') => Self::M,
+            (_, b'd') => Self::D,
+            _ => Self::Start,
+        };
+
+        Step::Next(next)
     }
+}
+
+fn interpret(input: &str, instruction_set: &impl InstructionSet) -> u64 {
+    let program = parse(input);
+    let execution = evaluate(&program, instruction_set);
 
-    Some(total_result)
+    advent_of_code::trace!("{}", highlight(input, &program, &execution.executed));
+
+    execution.total
 }
 
-#[derive(Debug)]
-enum Token {
-    OpenParen,
-    CloseParen,
-    Comma,
-    Mul,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Mul(u32, u32),
     Do,
     Dont,
-    Number(u32),
-    WhiteSpace,
-    Invalid,
-    Eof,
-}
-
-fn tokenizer(input: &str) -> Vec<Token> {
-    let mut tokens: Vec<Token> = Vec::new();
-    let mut iter = input.chars().peekable();
-
-    while let Some(ch) = iter.next() {
-        match ch {
-            '(' => tokens.push(Token::OpenParen),
-            ')' => tokens.push(Token::CloseParen),
-            ',' => tokens.push(Token::Comma),
-            'm' if matches_suffix(&mut iter, "ul") => {
-                tokens.push(Token::Mul);
-            }
-            'd' => {
-                if matches_suffix(&mut iter, "o") {
-                    if matches_suffix(&mut iter, "n't()") {
-                        tokens.push(Token::Dont);
-                    } else if matches_suffix(&mut iter, "()") {
-                        tokens.push(Token::Do);
-                    } else {
-                        tokens.push(Token::Invalid);
-                    }
-                } else {
-                    tokens.push(Token::Invalid);
-                }
+}
+
+/// A recognized instruction and the byte range of the input it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Spanned {
+    instruction: Instruction,
+    span: Range<usize>,
+}
+
+/// Extracts all well-formed instructions from corrupted memory.
+/// If an instruction does not parse, parsing resumes at the next byte, so a broken instruction never swallows
+/// a valid one that starts inside of it, e.g. `mul(1,mul(2,3))`.
+fn parse(input: &str) -> Vec<Spanned> {
+    let mut program = Vec::new();
+    let mut pos = 0;
+
+    while pos < input.len() {
+        let mut cursor = Cursor {
+            bytes: input.as_bytes(),
+            pos,
+        };
+
+        match cursor.instruction() {
+            Some(instruction) => {
+                program.push(Spanned {
+                    instruction,
+                    span: pos..cursor.pos,
+                });
+                pos = cursor.pos;
             }
-            first_digit @ '1'..='9' => {
-                let other_digits: String =
-                    from_fn(|| iter.by_ref().next_if(|c| c.is_ascii_digit())).collect();
+            None => pos += 1,
+        }
+    }
 
-                let num = format!("{first_digit}{other_digits}");
+    program
+}
 
-                match num.parse::<u32>() {
-                    Ok(n) if num.len() <= 3 => tokens.push(Token::Number(n)),
-                    _ => tokens.push(Token::Invalid),
-                }
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn instruction(&mut self) -> Option<Instruction> {
+        let start = self.pos;
+
+        if self.keyword("mul(") {
+            let a = self.number()?;
+            self.expect(",")?;
+            let b = self.number()?;
+            self.expect(")")?;
+            return Some(Instruction::Mul(a, b));
+        }
+
+        for (keyword, instruction) in [("do()", Instruction::Do), ("don't()", Instruction::Dont)] {
+            self.pos = start;
+            if self.keyword(keyword) {
+                return Some(instruction);
             }
-            ch if ch.is_whitespace() => tokens.push(Token::WhiteSpace),
-            _ => tokens.push(Token::Invalid),
         }
+
+        None
     }
 
-    tokens.push(Token::Eof);
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let matches = self.bytes[self.pos..].starts_with(keyword.as_bytes());
+        if matches {
+            self.pos += keyword.len();
+        }
+        matches
+    }
 
-    tokens
+    fn expect(&mut self, keyword: &str) -> Option<()> {
+        self.keyword(keyword).then_some(())
+    }
+
+    /// A number of one to three digits.
+    fn number(&mut self) -> Option<u32> {
+        let digits = self.bytes[self.pos..]
+            .iter()
+            .take(4)
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+
+        if !(1..=3).contains(&digits) {
+            return None;
+        }
+
+        let number = self.bytes[self.pos..self.pos + digits]
+            .iter()
+            .fold(0, |acc, b| acc * 10 + u32::from(b - b'0'));
+        self.pos += digits;
+        Some(number)
+    }
+}
+
+/// State of the machine that runs a program.
+struct Machine {
+    total: u64,
+    enabled: bool,
+}
+
+/// Decides what instructions do. Returns whether the instruction had an effect.
+trait InstructionSet {
+    fn execute(&self, machine: &mut Machine, instruction: Instruction) -> bool;
+}
+
+/// Part one: only multiplications are understood.
+struct MulOnly;
+
+impl InstructionSet for MulOnly {
+    fn execute(&self, machine: &mut Machine, instruction: Instruction) -> bool {
+        match instruction {
+            Instruction::Mul(a, b) => {
+                machine.total += u64::from(a * b);
+                true
+            }
+            Instruction::Do | Instruction::Dont => false,
+        }
+    }
 }
 
-fn matches_suffix(iter: &mut std::iter::Peekable<std::str::Chars>, keyword: &str) -> bool {
-    for expected in keyword.chars() {
-        if iter.next_if_eq(&expected).is_none() {
-            return false;
+/// Part two: `do()` and `don't()` enable and disable multiplications.
+struct Conditional;
+
+impl InstructionSet for Conditional {
+    fn execute(&self, machine: &mut Machine, instruction: Instruction) -> bool {
+        match instruction {
+            Instruction::Mul(a, b) if machine.enabled => {
+                machine.total += u64::from(a * b);
+                true
+            }
+            Instruction::Mul(..) => false,
+            Instruction::Do => {
+                machine.enabled = true;
+                true
+            }
+            Instruction::Dont => {
+                machine.enabled = false;
+                true
+            }
         }
     }
-    true
 }
 
-fn process_seq(seq: &[Token]) -> Option<(u32, u32)> {
-    use Token::*;
+struct Execution {
+    total: u64,
+    /// Whether each instruction of the program had an effect.
+    executed: Vec<bool>,
+}
+
+fn evaluate(program: &[Spanned], instruction_set: &impl InstructionSet) -> Execution {
+    let mut machine = Machine {
+        total: 0,
+        enabled: true,
+    };
+
+    let executed = program
+        .iter()
+        .map(|spanned| instruction_set.execute(&mut machine, spanned.instruction))
+        .collect();
+
+    Execution {
+        total: machine.total,
+        executed,
+    }
+}
+
+/// The input with executed instructions in green, skipped instructions in red and everything else dimmed.
+fn highlight(input: &str, program: &[Spanned], executed: &[bool]) -> String {
+    const DIM: &str = "\x1b[2m";
+    const GREEN: &str = "\x1b[1;32m";
+    const RED: &str = "\x1b[31m";
+    const RESET: &str = "\x1b[0m";
+
+    let mut output = String::with_capacity(input.len() * 2);
+
+    // NOTE: trace output is labelled per line, so colors are re-applied after every line break.
+    let mut push = |color: &str, text: &str| {
+        output.push_str(color);
+        output.push_str(&text.replace('\n', &format!("{RESET}\n{color}")));
+        output.push_str(RESET);
+    };
+
+    let mut pos = 0;
+
+    for (spanned, executed) in program.iter().zip(executed) {
+        push(DIM, &input[pos..spanned.span.start]);
+        push(
+            if *executed { GREEN } else { RED },
+            &input[spanned.span.clone()],
+        );
+        pos = spanned.span.end;
+    }
+
+    push(DIM, input[pos..].trim_end());
+    output
+}
+
+/// Generates `size` lines of corrupted memory, each with a few valid, broken and conditional instructions.
+pub fn generate(seed: u64, size: usize) -> String {
+    const JUNK: &[u8] = b"xm%&[]!@^_+ ()<>,'?#$*{}:;what~from-selectwhy/when";
+    const BROKEN: &[&str] = &[
+        "mul[3,7]",
+        "mul(32,64]",
+        "mul ( 2 , 4 )",
+        "mul(1234,5)",
+        "mul(5,)",
+        "mul(,5)",
+        "mul(4*",
+        "do_not_mul(5,5)",
+        "don't(",
+        "do(",
+    ];
 
-    match seq {
-        [Mul, OpenParen, Number(num1), Comma, Number(num2), CloseParen] => Some((*num1, *num2)),
-        _ => None,
+    let mut rng = Rng::new(seed);
+    let mut output = String::with_capacity(size * 80);
+
+    for _ in 0..size {
+        for _ in 0..rng.range(4..=8) {
+            for _ in 0..rng.range(0..=6) {
+                output.push(char::from(*rng.choose(JUNK)));
+            }
+
+            match rng.range(0..=8) {
+                0..=4 => output.push_str(&format!(
+                    "mul({},{})",
+                    rng.range(0..=999),
+                    rng.range(0..=999)
+                )),
+                5 | 6 => {
+                    let broken: &&str = rng.choose(BROKEN);
+                    output.push_str(broken);
+                }
+                7 => output.push_str("do()"),
+                _ => output.push_str("don't()"),
+            }
+        }
+        output.push('\n');
     }
+
+    output
 }
 
 #[cfg(test)]
@@ -158,4 +430,86 @@ mod tests {
         ));
         assert_eq!(result, Some(48));
     }
+
+    #[test]
+    fn test_part_one_interpreter() {
+        let result = part_one_interpreter(&advent_of_code::template::read_file_part(
+            "examples", DAY, 1,
+        ));
+        assert_eq!(result, Some(161));
+    }
+
+    #[test]
+    fn test_part_two_interpreter() {
+        let result = part_two_interpreter(&advent_of_code::template::read_file_part(
+            "examples", DAY, 2,
+        ));
+        assert_eq!(result, Some(48));
+    }
+
+    /// Hands out at most `chunk` bytes per read, so instructions get split across reads.
+    struct Chunked<'a> {
+        bytes: &'a [u8],
+        chunk: usize,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.chunk.min(buf.len()).min(self.bytes.len());
+            buf[..n].copy_from_slice(&self.bytes[..n]);
+            self.bytes = &self.bytes[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_scan() {
+        let input = "mul(1,mul(2,3))do(don't()mul(1234,5)mmul(4,5)do()mul(6,7)ddon't()mul(8,9)";
+        assert_eq!(scan(input.as_bytes(), &MulOnly).unwrap(), 6 + 20 + 42 + 72);
+        assert_eq!(scan(input.as_bytes(), &Conditional).unwrap(), 6 + 42);
+
+        for chunk in 1..=7 {
+            let reader = Chunked {
+                bytes: input.as_bytes(),
+                chunk,
+            };
+            assert_eq!(scan(reader, &Conditional).unwrap(), 6 + 42);
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        let input = generate(42, 100);
+        assert_eq!(input, generate(42, 100));
+        assert_eq!(input.lines().count(), 100);
+        assert_eq!(part_one(&input), part_one_interpreter(&input));
+        assert_eq!(part_two(&input), part_two_interpreter(&input));
+    }
+
+    #[test]
+    fn test_parse_recovers() {
+        let program = parse("mul(1,mul(2,3))do(don't()mul(1234,5)");
+        assert_eq!(
+            program,
+            vec![
+                Spanned {
+                    instruction: Instruction::Mul(2, 3),
+                    span: 6..14,
+                },
+                Spanned {
+                    instruction: Instruction::Dont,
+                    span: 18..25,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let program = parse("mul(2,3)don't()mul(4,5)do()");
+        let execution = evaluate(&program, &Conditional);
+        assert_eq!(execution.total, 6);
+        assert_eq!(execution.executed, vec![true, true, false, true]);
+        assert_eq!(evaluate(&program, &MulOnly).total, 26);
+    }
 }
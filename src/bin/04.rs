@@ -1,99 +1,371 @@
+use std::fmt;
+
 advent_of_code::solution!(4);
 
-const TARGET: &[char] = &['X', 'M', 'A', 'S'];
-const DIRECTIONS: [(isize, isize); 8] = [
-    (0, 1),
-    (0, -1),
-    (1, 0),
-    (-1, 0),
-    (1, 1),
-    (-1, -1),
-    (1, -1),
-    (-1, 1),
-];
-
-pub fn part_one(input: &str) -> Option<u32> {
-    let grid = parse_grid(input);
-    let mut count = 0;
-
-    for (i, row) in grid.iter().enumerate() {
-        for (j, &char) in row.iter().enumerate() {
-            if char != 'X' {
-                continue;
-            }
+const X_MAS: &str = "M.S\n.A.\nM.S";
+
+pub fn part_one(input: &str) -> Option<usize> {
+    let grid = Grid::parse(input)?;
+    let matches = grid.find_words(&["XMAS"], false);
+
+    for found in &matches {
+        advent_of_code::trace!("{found}");
+    }
+    advent_of_code::trace!(
+        "{}",
+        grid.render(matches.iter().flat_map(|found| &found.cells))
+    );
+
+    Some(matches.len())
+}
+
+pub fn part_two(input: &str) -> Option<usize> {
+    let grid = Grid::parse(input)?;
+    let matches = grid.find_stencil(&Stencil::parse(X_MAS)?, false);
+
+    for found in &matches {
+        advent_of_code::trace!("{found}");
+    }
+    advent_of_code::trace!(
+        "{}",
+        grid.render(matches.iter().flat_map(|found| &found.cells))
+    );
+
+    Some(matches.len())
+}
+
+/// A cell of the grid as `(row, column)`.
+type Position = (usize, usize);
+
+struct Grid {
+    cells: Vec<Vec<u8>>,
+    rows: usize,
+    cols: usize,
+}
+
+impl Grid {
+    /// `None` if the input is empty or not rectangular.
+    fn parse(input: &str) -> Option<Self> {
+        let cells: Vec<Vec<u8>> = input
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.bytes().collect())
+            .collect();
+
+        let rows = cells.len();
+        let cols = cells.first()?.len();
+        if cols == 0 || cells.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+
+        Some(Self { cells, rows, cols })
+    }
+
+    fn at(&self, (row, col): Position) -> u8 {
+        self.cells[row][col]
+    }
+
+    /// The position `steps` times `offset` away from `start`.
+    /// With `wrap`, positions leaving the grid re-enter it on the opposite side, otherwise they are `None`.
+    fn offset(
+        &self,
+        start: Position,
+        offset: (isize, isize),
+        steps: usize,
+        wrap: bool,
+    ) -> Option<Position> {
+        let steps = isize::try_from(steps).ok()?;
+        let row = isize::try_from(start.0).ok()? + offset.0 * steps;
+        let col = isize::try_from(start.1).ok()? + offset.1 * steps;
+
+        if wrap {
+            let rows = isize::try_from(self.rows).ok()?;
+            let cols = isize::try_from(self.cols).ok()?;
+            Some((row.rem_euclid(rows) as usize, col.rem_euclid(cols) as usize))
+        } else {
+            let position = (usize::try_from(row).ok()?, usize::try_from(col).ok()?);
+            (position.0 < self.rows && position.1 < self.cols).then_some(position)
+        }
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.rows).flat_map(move |row| (0..self.cols).map(move |col| (row, col)))
+    }
+
+    /// Every occurrence of every word, read in any of the 8 directions.
+    /// A one-letter word reads the same in every direction, so it is reported once, reading east.
+    /// With `wrap`, a word may not use a cell twice.
+    fn find_words<'a>(&self, words: &[&'a str], wrap: bool) -> Vec<WordMatch<'a>> {
+        let mut matches = vec![];
 
-            for &(di, dj) in &DIRECTIONS {
-                if check_target(&grid, i, j, di, dj, TARGET) {
-                    count += 1;
+        for start in self.positions() {
+            for &word in words {
+                if word.as_bytes().first() != Some(&self.at(start)) {
+                    continue;
+                }
+
+                let directions: &[Direction] = if word.len() == 1 {
+                    &[Direction::East]
+                } else {
+                    &Direction::ALL
+                };
+
+                for &direction in directions {
+                    let cells = word
+                        .bytes()
+                        .enumerate()
+                        .map(|(steps, letter)| {
+                            self.offset(start, direction.offset(), steps, wrap)
+                                .filter(|&position| self.at(position) == letter)
+                        })
+                        .collect::<Option<Vec<_>>>()
+                        .filter(|cells| {
+                            cells
+                                .iter()
+                                .enumerate()
+                                .all(|(i, cell)| !cells[..i].contains(cell))
+                        });
+
+                    if let Some(cells) = cells {
+                        matches.push(WordMatch {
+                            word,
+                            direction,
+                            cells,
+                        });
+                    }
                 }
             }
         }
+
+        matches
     }
 
-    Some(count)
-}
+    /// Every occurrence of the stencil in any of its distinct orientations.
+    fn find_stencil(&self, stencil: &Stencil, wrap: bool) -> Vec<StencilMatch> {
+        let mut matches = vec![];
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let grid = parse_grid(input);
-    let mut count = 0;
+        for (orientation, oriented) in stencil.orientations() {
+            for origin in self.positions() {
+                let cells = oriented
+                    .cells
+                    .iter()
+                    .map(|&(offset, letter)| {
+                        self.offset(origin, offset, 1, wrap)
+                            .filter(|&position| self.at(position) == letter)
+                    })
+                    .collect::<Option<Vec<_>>>();
 
-    for (i, row) in grid.iter().enumerate() {
-        for (j, &char) in row.iter().enumerate() {
-            if char == 'A' && is_valid_x_mas_center(&grid, i, j) {
-                count += 1;
+                if let Some(cells) = cells {
+                    matches.push(StencilMatch {
+                        origin,
+                        orientation,
+                        cells,
+                    });
+                }
             }
         }
+
+        matches
     }
 
-    Some(count)
+    /// The grid with the given cells highlighted and everything else dimmed.
+    fn render<'a>(&self, highlighted: impl IntoIterator<Item = &'a Position>) -> String {
+        const DIM: &str = "\x1b[2m";
+        const HIGHLIGHT: &str = "\x1b[1;32m";
+        const RESET: &str = "\x1b[0m";
+
+        let mut marked = vec![vec![false; self.cols]; self.rows];
+        for &(row, col) in highlighted {
+            marked[row][col] = true;
+        }
+
+        let mut output = String::with_capacity(self.rows * (self.cols + 1) * 8);
+        for (row, line) in self.cells.iter().enumerate() {
+            if row > 0 {
+                output.push('\n');
+            }
+            for (col, &letter) in line.iter().enumerate() {
+                output.push_str(if marked[row][col] { HIGHLIGHT } else { DIM });
+                output.push(char::from(letter));
+                output.push_str(RESET);
+            }
+        }
+
+        output
+    }
 }
 
-fn parse_grid(input: &str) -> Vec<Vec<char>> {
-    input.lines().map(|line| line.chars().collect()).collect()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
 }
 
-fn check_target(
-    grid: &[Vec<char>],
-    i: usize,
-    j: usize,
-    di: isize,
-    dj: isize,
-    target: &[char],
-) -> bool {
-    for (k, &ch) in target.iter().enumerate() {
-        let ni = i as isize + k as isize * di;
-        let nj = j as isize + k as isize * dj;
+impl Direction {
+    const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
 
-        if !in_bounds(grid, ni, nj) || grid[ni as usize][nj as usize] != ch {
-            return false;
+    /// `(row, column)` step.
+    fn offset(self) -> (isize, isize) {
+        match self {
+            Self::North => (-1, 0),
+            Self::NorthEast => (-1, 1),
+            Self::East => (0, 1),
+            Self::SouthEast => (1, 1),
+            Self::South => (1, 0),
+            Self::SouthWest => (1, -1),
+            Self::West => (0, -1),
+            Self::NorthWest => (-1, -1),
         }
     }
-    true
 }
 
-fn in_bounds(grid: &[Vec<char>], ni: isize, nj: isize) -> bool {
-    ni >= 0 && nj >= 0 && ni < grid.len() as isize && nj < grid[0].len() as isize
+struct WordMatch<'a> {
+    word: &'a str,
+    direction: Direction,
+    /// Cells of the letters of the word, in order.
+    cells: Vec<Position>,
+}
+
+impl fmt::Display for WordMatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (row, col) = self.cells[0];
+        write!(
+            f,
+            "{} at ({row}, {col}) reading {:?}",
+            self.word, self.direction
+        )
+    }
+}
+
+/// A 2D pattern of letters, `.` matches any letter. Cells are stored relative to the top left corner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stencil {
+    cells: Vec<((isize, isize), u8)>,
 }
 
-fn is_valid_x_mas_center(grid: &[Vec<char>], i: usize, j: usize) -> bool {
-    let rows = grid.len();
-    let cols = grid[0].len();
+impl Stencil {
+    /// `None` if the pattern has no letters, as it would match everywhere.
+    fn parse(pattern: &str) -> Option<Self> {
+        let cells: Vec<_> = pattern
+            .lines()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.bytes()
+                    .enumerate()
+                    .filter(|&(_, letter)| letter != b'.')
+                    .map(move |(col, letter)| ((row as isize, col as isize), letter))
+            })
+            .collect();
 
-    // Ensure it's not near the edges
-    if i < 1 || i >= rows - 1 || j < 1 || j >= cols - 1 {
-        return false;
+        (!cells.is_empty()).then(|| Self::normalized(cells))
     }
 
-    // Check diagonals for `MAS` or `SAM` patterns
-    let tl_br = (grid[i - 1][j - 1], grid[i + 1][j + 1]);
-    let tr_bl = (grid[i - 1][j + 1], grid[i + 1][j - 1]);
+    /// Moves the stencil to the top left corner and sorts its cells, so equal shapes compare equal.
+    fn normalized(mut cells: Vec<((isize, isize), u8)>) -> Self {
+        let min_row = cells.iter().map(|((row, _), _)| *row).min().unwrap_or(0);
+        let min_col = cells.iter().map(|((_, col), _)| *col).min().unwrap_or(0);
 
-    matches_mas_or_sam(tl_br) && matches_mas_or_sam(tr_bl)
+        for ((row, col), _) in &mut cells {
+            *row -= min_row;
+            *col -= min_col;
+        }
+        cells.sort_unstable();
+
+        Self { cells }
+    }
+
+    fn transformed(&self, orientation: Orientation) -> Self {
+        let cells = self
+            .cells
+            .iter()
+            .map(|&((row, col), letter)| {
+                let (mut row, mut col) = if orientation.reflected {
+                    (row, -col)
+                } else {
+                    (row, col)
+                };
+                for _ in 0..orientation.quarter_turns {
+                    (row, col) = (col, -row);
+                }
+                ((row, col), letter)
+            })
+            .collect();
+
+        Self::normalized(cells)
+    }
+
+    /// The distinct rotations and reflections of the stencil, each with the first orientation that produces it.
+    fn orientations(&self) -> Vec<(Orientation, Stencil)> {
+        let mut orientations: Vec<(Orientation, Stencil)> = vec![];
+
+        for orientation in Orientation::all() {
+            let stencil = self.transformed(orientation);
+            if !orientations.iter().any(|(_, known)| *known == stencil) {
+                orientations.push((orientation, stencil));
+            }
+        }
+
+        orientations
+    }
 }
 
-/// Checks if a diagonal pair matches `MAS` or `SAM`.
-fn matches_mas_or_sam(pair: (char, char)) -> bool {
-    matches!(pair, ('M', 'S') | ('S', 'M'))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Orientation {
+    /// Clockwise quarter turns, applied after the reflection.
+    quarter_turns: u8,
+    /// Mirrored left to right.
+    reflected: bool,
+}
+
+impl Orientation {
+    fn all() -> impl Iterator<Item = Self> {
+        [false, true].into_iter().flat_map(|reflected| {
+            (0..4).map(move |quarter_turns| Self {
+                quarter_turns,
+                reflected,
+            })
+        })
+    }
+}
+
+impl fmt::Display for Orientation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.reflected, self.quarter_turns) {
+            (false, 0) => write!(f, "upright"),
+            (false, turns) => write!(f, "rotated {}°", u32::from(turns) * 90),
+            (true, 0) => write!(f, "reflected"),
+            (true, turns) => write!(f, "reflected, rotated {}°", u32::from(turns) * 90),
+        }
+    }
+}
+
+struct StencilMatch {
+    /// Grid position of the top left corner of the oriented stencil.
+    origin: Position,
+    orientation: Orientation,
+    cells: Vec<Position>,
+}
+
+impl fmt::Display for StencilMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (row, col) = self.origin;
+        write!(f, "stencil at ({row}, {col}) {}", self.orientation)
+    }
 }
 
 #[cfg(test)]
@@ -111,4 +383,90 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(9));
     }
+
+    #[test]
+    fn test_find_words() {
+        let grid = Grid::parse("ABC\nDEF\nGHI").unwrap();
+
+        let matches = grid.find_words(&["AEI", "FD", "CB"], false);
+        let found: Vec<(&str, Direction)> = matches.iter().map(|m| (m.word, m.direction)).collect();
+        assert_eq!(
+            found,
+            vec![("AEI", Direction::SouthEast), ("CB", Direction::West)]
+        );
+        assert_eq!(matches[0].cells, vec![(0, 0), (1, 1), (2, 2)]);
+
+        let matches = grid.find_words(&["FD"], true);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].direction, Direction::East);
+        assert_eq!(matches[0].cells, vec![(1, 2), (1, 0)]);
+    }
+
+    #[test]
+    fn test_find_single_letters() {
+        let grid = Grid::parse("AB\nBA").unwrap();
+
+        let matches = grid.find_words(&["A"], false);
+        let cells: Vec<Position> = matches.iter().map(|m| m.cells[0]).collect();
+        assert_eq!(cells, vec![(0, 0), (1, 1)]);
+        assert!(matches.iter().all(|m| m.direction == Direction::East));
+    }
+
+    #[test]
+    fn test_find_words_without_reusing_cells() {
+        let grid = Grid::parse("AA").unwrap();
+
+        // reading north or south wraps back onto the same cell, diagonals wrap onto the other one.
+        let matches = grid.find_words(&["AA"], true);
+        let directions: Vec<Direction> = matches
+            .iter()
+            .filter(|m| m.cells[0] == (0, 0))
+            .map(|m| m.direction)
+            .collect();
+        assert_eq!(
+            directions,
+            vec![
+                Direction::NorthEast,
+                Direction::East,
+                Direction::SouthEast,
+                Direction::SouthWest,
+                Direction::West,
+                Direction::NorthWest,
+            ]
+        );
+
+        assert!(grid.find_words(&["AAA"], true).is_empty());
+    }
+
+    #[test]
+    fn test_stencil_orientations() {
+        assert_eq!(Stencil::parse(X_MAS).unwrap().orientations().len(), 4);
+        assert_eq!(Stencil::parse("AB\nB.").unwrap().orientations().len(), 4);
+        assert_eq!(Stencil::parse("AB\n.C").unwrap().orientations().len(), 8);
+
+        let grid = Grid::parse("..\nA.\nBC").unwrap();
+        let matches = grid.find_stencil(&Stencil::parse("AB\n.C").unwrap(), false);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].origin, (1, 0));
+        assert_eq!(matches[0].cells, vec![(1, 0), (2, 0), (2, 1)]);
+        assert_eq!(
+            matches[0].orientation.to_string(),
+            "reflected, rotated 270°"
+        );
+    }
+
+    #[test]
+    fn test_empty_stencil() {
+        assert_eq!(Stencil::parse(""), None);
+        assert_eq!(Stencil::parse("..\n.."), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let grid = Grid::parse("AB\nCD").unwrap();
+        assert_eq!(
+            grid.render(&[(0, 1)]),
+            "\x1b[2mA\x1b[0m\x1b[1;32mB\x1b[0m\n\x1b[2mC\x1b[0m\x1b[2mD\x1b[0m"
+        );
+    }
 }
@@ -1,173 +1,430 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fmt;
 
-advent_of_code::solution!(5);
+use advent_of_code::template::generator::Rng;
 
-type PeekableLineIterator<'a> = std::iter::Peekable<std::str::Lines<'a>>;
+advent_of_code::solution!(5, generate);
 
 pub fn part_one(input: &str) -> Option<u32> {
-    let mut lines = input.lines().peekable();
-    let ordering_rules = parse_rules(&mut lines);
+    let (rules, updates) = parse(input)?;
+
+    Some(
+        updates
+            .iter()
+            .filter(|update| rules.is_ordered(update))
+            .map(|update| middle_page_number(update))
+            .sum(),
+    )
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    let (rules, updates) = parse(input)?;
 
     let mut total = 0_u32;
-    for update_str in lines {
-        let update: Vec<&str> = update_str.split(',').collect();
-        if is_valid_update(&update, &ordering_rules) {
-            total += middle_page_number(&update).unwrap_or(0);
+
+    for update in &updates {
+        if rules.is_ordered(update) {
+            continue;
         }
+
+        let sorted = match rules.sort(update) {
+            Ok(sorted) => sorted,
+            Err(cycle) => {
+                advent_of_code::trace!(
+                    "Update {} can not be ordered, its rules contain a cycle: {cycle} (conflicting rules: {})",
+                    join(update, ","),
+                    join(&cycle.rules(), ", ")
+                );
+                return None;
+            }
+        };
+
+        advent_of_code::trace!(
+            "{} violates {}, reordered to {}",
+            join(update, ","),
+            rules
+                .restrict(update)
+                .explain()
+                .map(|rules| join(&rules, ", "))
+                .unwrap_or_default(),
+            join(&sorted, ",")
+        );
+
+        total += middle_page_number(&sorted);
     }
 
     Some(total)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let mut lines = input.lines().peekable();
-    let ordering_rules = parse_rules(&mut lines);
+/// Page numbers have at most two digits.
+type Page = u8;
 
-    let mut total = 0_u32;
+const PAGES: usize = 100;
 
-    for update_str in lines {
-        let mut update: Vec<&str> = update_str.split(',').collect();
+fn parse(input: &str) -> Option<(Rules, Vec<Vec<Page>>)> {
+    let (rules, updates) = input.split_once("\n\n")?;
 
-        if is_valid_update(&update, &ordering_rules) {
-            continue;
-        }
+    let rules = Rules::parse(rules)?;
+    let updates = updates
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.split(',').map(parse_page).collect())
+        .collect::<Option<_>>()?;
 
-        let dag = build_update_dag(&update, &ordering_rules);
-        let sorted = topological_sort(&dag)?;
+    Some((rules, updates))
+}
 
-        let position: HashMap<&str, usize> = sorted
-            .iter()
-            .enumerate()
-            .map(|(i, &page)| (page, i))
-            .collect();
+fn parse_page(page: &str) -> Option<Page> {
+    page.parse().ok().filter(|&page| usize::from(page) < PAGES)
+}
 
-        update.sort_by_key(|p| position[p]);
+fn join<T: fmt::Display>(items: &[T], separator: &str) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// An ordering rule `before|after`: if both pages are part of an update, `before` has to be printed first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rule {
+    before: Page,
+    after: Page,
+}
 
-        total += middle_page_number(&update).unwrap_or(0);
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}|{}", self.before, self.after)
     }
+}
 
-    Some(total)
+/// A partial order on pages, given by ordering rules.
+/// Stored as two 100x100 bitsets, so comparing two pages is a single bit test.
+struct Rules {
+    /// Bit `after` of `successors[before]` is set for every rule `before|after`.
+    successors: [u128; PAGES],
+    /// The transposed bitset: bit `before` of `predecessors[after]`.
+    predecessors: [u128; PAGES],
 }
 
-fn parse_rules<'a>(lines: &mut PeekableLineIterator<'a>) -> HashMap<&'a str, HashSet<&'a str>> {
-    let mut ordering_rules: HashMap<&'a str, HashSet<&'a str>> = HashMap::new();
+impl Rules {
+    fn parse(input: &str) -> Option<Self> {
+        let mut rules = Self {
+            successors: [0; PAGES],
+            predecessors: [0; PAGES],
+        };
+
+        for line in input.lines().filter(|line| !line.is_empty()) {
+            let (before, after) = line.split_once('|')?;
+            let (before, after) = (parse_page(before)?, parse_page(after)?);
+            rules.successors[usize::from(before)] |= 1 << after;
+            rules.predecessors[usize::from(after)] |= 1 << before;
+        }
+
+        Some(rules)
+    }
+
+    fn precedes(&self, before: Page, after: Page) -> bool {
+        self.successors[usize::from(before)] & (1 << after) != 0
+    }
 
-    while let Some(line) = lines.next_if(|l| !l.is_empty()) {
-        if let Some((left, right)) = line.split_once('|') {
-            ordering_rules.entry(left).or_default().insert(right);
+    fn compare(&self, a: Page, b: Page) -> Ordering {
+        if self.precedes(a, b) {
+            Ordering::Less
+        } else if self.precedes(b, a) {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
         }
     }
 
-    assert!(matches!(lines.next(), Some("")));
+    /// Whether the rules order every pair of pages of the update, without contradicting each other.
+    /// Only then [`Rules::compare`] is a total order on the update and sorting with it is well-defined.
+    fn is_total_order(&self, update: &[Page]) -> bool {
+        let mask = update.iter().fold(0_u128, |mask, &page| mask | 1 << page);
+        if mask.count_ones() as usize != update.len() {
+            return false;
+        }
 
-    ordering_rules
-}
+        // NOTE: without contradicting rules every pair of pages has at most one rule. Then every pair has exactly one
+        // if the number of successors within the update is different for every page (n - 1, n - 2, …, 0),
+        // and a tournament like that has no cycles.
+        let mut seen = 0_u128;
+        for &page in update {
+            let page = usize::from(page);
+            if self.successors[page] & self.predecessors[page] & mask != 0 {
+                return false;
+            }
 
-fn is_valid_update(update: &[&str], ordering_rules: &HashMap<&str, HashSet<&str>>) -> bool {
-    let index_map: HashMap<&str, usize> = update
-        .iter()
-        .enumerate()
-        .map(|(i, &page)| (page, i))
-        .collect();
-
-    for (x, ys) in ordering_rules {
-        for &y in ys {
-            if let (Some(&x_idx), Some(&y_idx)) = (index_map.get(x), index_map.get(y)) {
-                if x_idx > y_idx {
-                    return false;
-                }
+            let successors = (self.successors[page] & mask).count_ones();
+            if seen & (1 << successors) != 0 {
+                return false;
             }
+            seen |= 1 << successors;
+        }
+
+        true
+    }
+
+    /// Whether the update follows all rules.
+    fn is_ordered(&self, update: &[Page]) -> bool {
+        if self.is_total_order(update) {
+            update.is_sorted_by(|&a, &b| self.compare(a, b) != Ordering::Greater)
+        } else {
+            self.restrict(update).violations().is_empty()
         }
     }
 
-    true
+    /// The pages of the update in an order that satisfies all rules, see [`UpdateGraph::sort`].
+    fn sort(&self, update: &[Page]) -> Result<Vec<Page>, Cycle> {
+        if self.is_total_order(update) {
+            let mut sorted = update.to_vec();
+            sorted.sort_by(|&a, &b| self.compare(a, b));
+            Ok(sorted)
+        } else {
+            self.restrict(update).sort()
+        }
+    }
+
+    /// The rules that apply to the pages of an update.
+    fn restrict(&self, update: &[Page]) -> UpdateGraph {
+        let successors = update
+            .iter()
+            .map(|&before| {
+                (0..update.len())
+                    .filter(|&after| self.precedes(before, update[after]))
+                    .collect()
+            })
+            .collect();
+
+        UpdateGraph {
+            pages: update.to_vec(),
+            successors,
+        }
+    }
 }
 
-struct Dag<'a> {
-    adj: HashMap<&'a str, Vec<&'a str>>,
-    in_degree: HashMap<&'a str, usize>,
+/// The rules restricted to the pages of one update. Pages are referred to by their index in the update.
+/// Slower than comparing pages with [`Rules`], but also handles rules that do not form a total order.
+struct UpdateGraph {
+    pages: Vec<Page>,
+    successors: Vec<Vec<usize>>,
 }
 
-impl<'a> Dag<'a> {
-    pub fn new() -> Self {
-        Self {
-            adj: HashMap::new(),
-            in_degree: HashMap::new(),
+impl UpdateGraph {
+    fn rule(&self, before: usize, after: usize) -> Rule {
+        Rule {
+            before: self.pages[before],
+            after: self.pages[after],
         }
     }
 
-    pub fn add_edge(&mut self, u: &'a str, v: &'a str) {
-        self.adj.entry(u).or_default().push(v);
-        *self.in_degree.entry(v).or_insert(0) += 1;
-        self.in_degree.entry(u).or_insert(0);
+    /// Rules the update breaks by printing `after` before `before`.
+    fn violations(&self) -> Vec<Rule> {
+        self.edges()
+            .filter(|&(before, after)| before > after)
+            .map(|(before, after)| self.rule(before, after))
+            .collect()
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.successors
+            .iter()
+            .enumerate()
+            .flat_map(|(before, next)| next.iter().map(move |&after| (before, after)))
     }
-}
 
-fn build_update_dag<'a>(
-    update: &[&'a str],
-    ordering_rules: &HashMap<&'a str, HashSet<&'a str>>,
-) -> Dag<'a> {
-    let update_set: HashSet<&str> = update.iter().copied().collect();
-    let mut dag = Dag::new();
+    /// A minimal set of broken rules that explains why the update is invalid.
+    /// A broken rule is left out if other rules already imply it, e.g. `a|c` given `a|b` and `b|c`,
+    /// as fixing the update for the remaining rules fixes it as well.
+    fn explain(&self) -> Result<Vec<Rule>, Cycle> {
+        if let Some(cycle) = self.find_cycle() {
+            return Err(cycle);
+        }
 
-    for &p in update {
-        dag.in_degree.entry(p).or_insert(0);
-        dag.adj.entry(p).or_default();
+        Ok(self
+            .edges()
+            .filter(|&(before, after)| before > after && !self.is_implied(before, after))
+            .map(|(before, after)| self.rule(before, after))
+            .collect())
     }
 
-    for (x, ys) in ordering_rules {
-        if update_set.contains(x) {
-            for &y in ys {
-                if update_set.contains(y) {
-                    dag.add_edge(x, y);
+    /// Whether `after` can be reached from `before` without using the direct rule between them.
+    fn is_implied(&self, before: usize, after: usize) -> bool {
+        let mut visited = vec![false; self.pages.len()];
+        let mut stack: Vec<usize> = self.successors[before]
+            .iter()
+            .copied()
+            .filter(|&next| next != after)
+            .collect();
+
+        while let Some(page) = stack.pop() {
+            if page == after {
+                return true;
+            }
+            if !std::mem::replace(&mut visited[page], true) {
+                stack.extend(&self.successors[page]);
+            }
+        }
+
+        false
+    }
+
+    /// The pages in an order that satisfies all rules.
+    /// Whenever several pages could come next, the one printed first in the update is taken,
+    /// so the result is deterministic and pages the rules do not constrain keep their relative order.
+    fn sort(&self) -> Result<Vec<Page>, Cycle> {
+        let mut in_degree = vec![0; self.pages.len()];
+        for (_, after) in self.edges() {
+            in_degree[after] += 1;
+        }
+
+        let mut ready: BTreeSet<usize> = (0..self.pages.len())
+            .filter(|&page| in_degree[page] == 0)
+            .collect();
+        let mut sorted = Vec::with_capacity(self.pages.len());
+
+        while let Some(page) = ready.pop_first() {
+            sorted.push(self.pages[page]);
+
+            for &next in &self.successors[page] {
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    ready.insert(next);
                 }
             }
         }
+
+        if sorted.len() == self.pages.len() {
+            Ok(sorted)
+        } else {
+            Err(self
+                .find_cycle()
+                .expect("pages left over by a topological sort are part of a cycle"))
+        }
     }
 
-    for &p in &update_set {
-        dag.in_degree.entry(p).or_insert(0);
-        dag.adj.entry(p).or_default();
+    fn find_cycle(&self) -> Option<Cycle> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            New,
+            OnPath,
+            Done,
+        }
+
+        let mut marks = vec![Mark::New; self.pages.len()];
+
+        for start in 0..self.pages.len() {
+            if marks[start] != Mark::New {
+                continue;
+            }
+
+            // iterative depth-first search, `path` holds each page on the path and the next successor to visit.
+            let mut path = vec![(start, 0)];
+            marks[start] = Mark::OnPath;
+
+            while let Some((page, next)) = path.last_mut() {
+                let Some(&successor) = self.successors[*page].get(*next) else {
+                    marks[*page] = Mark::Done;
+                    path.pop();
+                    continue;
+                };
+                *next += 1;
+
+                match marks[successor] {
+                    Mark::New => {
+                        marks[successor] = Mark::OnPath;
+                        path.push((successor, 0));
+                    }
+                    Mark::OnPath => {
+                        let from = path.iter().position(|&(p, _)| p == successor)?;
+                        return Some(Cycle {
+                            pages: path[from..].iter().map(|&(p, _)| self.pages[p]).collect(),
+                        });
+                    }
+                    Mark::Done => {}
+                }
+            }
+        }
+
+        None
     }
+}
 
-    dag
+/// Pages that each have to come before the next, and the last before the first.
+#[derive(Debug, PartialEq, Eq)]
+struct Cycle {
+    pages: Vec<Page>,
 }
 
-fn topological_sort<'a>(dag: &Dag<'a>) -> Option<Vec<&'a str>> {
-    let mut in_degree = dag.in_degree.clone();
-    let mut queue = VecDeque::new();
+impl Cycle {
+    /// The rules that form the cycle.
+    fn rules(&self) -> Vec<Rule> {
+        self.pages
+            .iter()
+            .zip(self.pages.iter().cycle().skip(1))
+            .map(|(&before, &after)| Rule { before, after })
+            .collect()
+    }
+}
 
-    for (&page, &deg) in &in_degree {
-        if deg == 0 {
-            queue.push_back(page);
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for page in &self.pages {
+            write!(f, "{page} → ")?;
         }
+        write!(f, "{}", self.pages[0])
     }
+}
 
-    let mut result = Vec::with_capacity(in_degree.len());
+fn middle_page_number(update: &[Page]) -> u32 {
+    u32::from(update[update.len() / 2])
+}
 
-    while let Some(node) = queue.pop_front() {
-        result.push(node);
+/// Generates ordering rules for every pair of 49 distinct pages and `size` updates of 5 to 23 pages.
+/// About half of the updates are shuffled.
+pub fn generate(seed: u64, size: usize) -> String {
+    const PAGES: usize = 49;
 
-        if let Some(adj_list) = dag.adj.get(node) {
-            for &v in adj_list {
-                let d = in_degree.get_mut(v).unwrap();
-                *d -= 1;
-                if *d == 0 {
-                    queue.push_back(v);
-                }
-            }
+    let mut rng = Rng::new(seed);
+    let mut output = String::with_capacity(PAGES * PAGES * 3 + size * 40);
+
+    let mut pages: Vec<u64> = (10..100).collect();
+    shuffle(&mut rng, &mut pages);
+    pages.truncate(PAGES);
+
+    for (i, before) in pages.iter().enumerate() {
+        for after in &pages[i + 1..] {
+            output.push_str(&format!("{before}|{after}\n"));
         }
     }
+    output.push('\n');
 
-    if result.len() == in_degree.len() {
-        Some(result)
-    } else {
-        None
+    for _ in 0..size {
+        let len = usize::try_from(rng.range(2..=11)).unwrap_or(2) * 2 + 1;
+        let mut update = pages.clone();
+        shuffle(&mut rng, &mut update);
+        update.truncate(len);
+
+        if rng.chance(0.5) {
+            update.sort_by_key(|page| pages.iter().position(|p| p == page));
+        }
+
+        let update: Vec<String> = update.iter().map(ToString::to_string).collect();
+        output.push_str(&update.join(","));
+        output.push('\n');
     }
+
+    output
 }
 
-fn middle_page_number(update: &[&str]) -> Option<u32> {
-    update[update.len() / 2].parse::<u32>().ok()
+fn shuffle<T>(rng: &mut Rng, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        let j = usize::try_from(rng.range(0..=i as u64)).unwrap_or(0);
+        items.swap(i, j);
+    }
 }
 
 #[cfg(test)]
@@ -185,4 +442,52 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(123));
     }
+
+    #[test]
+    fn test_explain() {
+        let rules = Rules::parse("75|47\n47|61\n75|61\n97|13").unwrap();
+        let graph = rules.restrict(&[61, 13, 47, 97, 75]);
+
+        assert_eq!(graph.violations().len(), 4);
+        assert_eq!(join(&graph.explain().unwrap(), ", "), "47|61, 97|13, 75|47");
+    }
+
+    #[test]
+    fn test_sort_is_stable() {
+        let rules = Rules::parse("3|1").unwrap();
+        assert!(!rules.is_total_order(&[5, 1, 4, 3, 2]));
+        assert_eq!(rules.sort(&[5, 1, 4, 3, 2]), Ok(vec![5, 4, 3, 1, 2]));
+        assert!(!rules.is_ordered(&[1, 5, 3]));
+    }
+
+    #[test]
+    fn test_total_order() {
+        let rules = Rules::parse("1|2\n2|3\n1|3\n3|1").unwrap();
+        assert!(rules.is_total_order(&[2, 1]));
+        assert!(!rules.is_total_order(&[1, 2, 3]));
+        assert!(!rules.is_total_order(&[1, 1]));
+        assert_eq!(rules.sort(&[2, 1]), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let rules = Rules::parse("1|2\n2|3\n3|1\n3|4").unwrap();
+        let cycle = rules.sort(&[4, 3, 2, 1]).unwrap_err();
+
+        assert_eq!(cycle.to_string(), "3 → 1 → 2 → 3");
+        assert_eq!(join(&cycle.rules(), ", "), "3|1, 1|2, 2|3");
+        assert_eq!(rules.restrict(&[4, 3, 2, 1]).explain(), Err(cycle));
+
+        assert_eq!(rules.sort(&[1, 2, 4]), Ok(vec![1, 2, 4]));
+    }
+
+    #[test]
+    fn test_generate() {
+        let input = generate(42, 100);
+        assert_eq!(input, generate(42, 100));
+
+        let (rules, updates) = parse(&input).unwrap();
+        assert_eq!(updates.len(), 100);
+        assert!(updates.iter().all(|update| rules.is_total_order(update)));
+    }
 }
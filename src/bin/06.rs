@@ -1,16 +1,96 @@
-use std::{collections::HashSet, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    num::NonZeroUsize,
+    ops::RangeInclusive,
+    str::FromStr,
+    thread,
+};
 
-advent_of_code::solution!(6);
+use advent_of_code::template::generator::Rng;
+use advent_of_code::template::visualize::Frame;
+
+advent_of_code::solution!(6, generate, alternatives = [part_two_naive => 2]);
 
 pub fn part_one(input: &str) -> Option<u32> {
     let mut grid: Grid = input.parse().ok()?;
+
+    advent_of_code::trace!("{}", to_text(&grid.annotate(&grid.walk(None).states, None)));
+    advent_of_code::visualize!(Frame::from_grid(
+        &grid.annotate(&grid.walk(None).states, None),
+        |&ch| ch
+    )
+    .with_caption("guard path"));
+
     let visited_positions = grid.simulate_1();
     Some(visited_positions.len() as u32)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
     let mut grid: Grid = input.parse().ok()?;
-    Some(grid.simulate_2() as u32)
+    let obstacles = grid.simulate_2();
+
+    for &obstacle in &obstacles {
+        advent_of_code::trace!("{}", grid.explain_loop(obstacle));
+        advent_of_code::visualize!(Frame::from_grid(&grid.loop_cells(obstacle), |&ch| ch)
+            .with_caption(format!("obstacle at {obstacle:?}")));
+    }
+
+    Some(obstacles.len() as u32)
+}
+
+/// Tries an obstacle on every empty cell instead of only on the guard's path.
+pub fn part_two_naive(input: &str) -> Option<u32> {
+    let mut grid: Grid = input.parse().ok()?;
+    let (start_pos, start_dir) = (grid.current_pos, grid.current_dir);
+    let mut count = 0;
+
+    for i in 0..grid.grid.len() {
+        for j in 0..grid.grid[i].len() {
+            if (i, j) == start_pos || matches!(grid.grid[i][j], Cell::Obstacle) {
+                continue;
+            }
+
+            grid.grid[i][j] = Cell::Obstacle;
+            grid.current_pos = start_pos;
+            grid.current_dir = start_dir;
+
+            if grid.check_for_loop() {
+                count += 1;
+            }
+
+            grid.grid[i][j] = Cell::Empty;
+        }
+    }
+
+    Some(count)
+}
+
+/// Generates a `size` x `size` map with obstacles on about 8% of the cells and the guard facing up.
+/// Like in puzzle inputs, the guard always leaves the map, maps on which it walks in a loop are generated again.
+pub fn generate(seed: u64, size: usize) -> String {
+    let size = size.max(1);
+    let mut rng = Rng::new(seed);
+
+    loop {
+        let start = rng.range(0..=(size * size - 1) as u64) as usize;
+        let mut output = String::with_capacity(size * (size + 1));
+
+        for i in 0..size {
+            for j in 0..size {
+                output.push(match i * size + j {
+                    idx if idx == start => '^',
+                    _ if rng.chance(0.08) => '#',
+                    _ => '.',
+                });
+            }
+            output.push('\n');
+        }
+
+        let mut grid: Grid = output.parse().expect("generated map is valid");
+        if !grid.check_for_loop() {
+            return output;
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -37,40 +117,201 @@ impl Grid {
         visited
     }
 
-    fn simulate_2(&mut self) -> usize {
-        let mut count = 0;
+    /// Obstacles that make the guard walk in a loop.
+    fn simulate_2(&mut self) -> Vec<(usize, usize)> {
+        let candidates = self.first_steps();
+        let jumps = JumpTable::new(self);
+        let states = self.grid.len() * self.grid[0].len() * 4;
+
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let chunk_size = candidates.len().div_ceil(threads).max(1);
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = candidates
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    let jumps = &jumps;
+                    scope.spawn(move || {
+                        // NOTE: states are marked with the number of the candidate instead of a fresh set per candidate.
+                        let mut seen = vec![0_u32; states];
+                        (1..)
+                            .zip(chunk)
+                            .filter(|&(stamp, candidate)| {
+                                jumps.is_loop(
+                                    candidate.pos,
+                                    candidate.dir,
+                                    candidate.obstacle,
+                                    &mut seen,
+                                    stamp,
+                                )
+                            })
+                            .map(|(_, candidate)| candidate.obstacle)
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        })
+    }
 
+    /// Every cell on the guard's path except the start, as a place for an obstacle.
+    fn first_steps(&mut self) -> Vec<Candidate> {
         let (start_pos, start_dir) = (self.current_pos, self.current_dir);
+        let mut visited = HashSet::from([start_pos]);
+        let mut steps = vec![];
+
+        while let Some(next_pos) = self.next_position() {
+            match self.grid[next_pos.0][next_pos.1] {
+                Cell::Obstacle => self.turn_right(),
+                Cell::Empty => {
+                    if visited.insert(next_pos) {
+                        steps.push(Candidate {
+                            obstacle: next_pos,
+                            pos: self.current_pos,
+                            dir: self.current_dir,
+                        });
+                    }
+                    self.current_pos = next_pos;
+                }
+            }
+        }
 
-        let visited_positions = self.simulate_1();
         self.current_pos = start_pos;
         self.current_dir = start_dir;
+        steps
+    }
 
-        for (i, j) in visited_positions {
-            if (i, j) == start_pos {
-                continue;
+    /// The guard's walk from the start, with an optional additional obstacle.
+    fn walk(&self, obstacle: Option<(usize, usize)>) -> Walk {
+        let mut state = (self.current_pos, self.current_dir);
+        let mut states = vec![state];
+        let mut seen = HashMap::from([(state, 0)]);
+
+        while let Some(next_pos) = self.step(state.0, state.1) {
+            state = if Some(next_pos) == obstacle
+                || matches!(self.grid[next_pos.0][next_pos.1], Cell::Obstacle)
+            {
+                (state.0, state.1.turn_right())
+            } else {
+                (next_pos, state.1)
+            };
+
+            if let Some(&loop_start) = seen.get(&state) {
+                return Walk {
+                    states,
+                    loop_start: Some(loop_start),
+                };
             }
+            seen.insert(state, states.len());
+            states.push(state);
+        }
 
-            self.grid[i][j] = Cell::Obstacle;
-
-            self.current_pos = start_pos;
-            self.current_dir = start_dir;
+        Walk {
+            states,
+            loop_start: None,
+        }
+    }
 
-            if self.check_for_loop() {
-                count += 1;
-            }
+    /// The map with the given states drawn like in the puzzle: `|` and `-` for walking up/down and left/right,
+    /// `+` where the guard turns or crosses its own path and `O` for the additional obstacle.
+    /// The guard's start is marked with its starting direction, e.g. `^`.
+    fn annotate(
+        &self,
+        states: &[((usize, usize), Direction)],
+        obstacle: Option<(usize, usize)>,
+    ) -> Vec<Vec<char>> {
+        let mut cells: Vec<Vec<char>> = self
+            .grid
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        Cell::Obstacle => '#',
+                        Cell::Empty => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for (i, &((row, col), dir)) in states.iter().enumerate() {
+            let turns = states
+                .get(i + 1)
+                .is_some_and(|&(next_pos, next_dir)| next_pos == (row, col) && next_dir != dir);
+            let line = match dir {
+                Direction::Up | Direction::Down => '|',
+                Direction::Left | Direction::Right => '-',
+            };
+
+            let cell = &mut cells[row][col];
+            *cell = match *cell {
+                _ if turns => '+',
+                '.' => line,
+                current if current == line => line,
+                _ => '+',
+            };
+        }
 
-            self.grid[i][j] = Cell::Empty;
+        if let Some((row, col)) = obstacle {
+            cells[row][col] = 'O';
         }
+        let (row, col) = self.current_pos;
+        cells[row][col] = self.current_dir.to_char();
+
+        cells
+    }
+
+    /// The loop an additional obstacle creates, cropped to the loop and its surroundings.
+    /// Empty if the obstacle does not create a loop.
+    fn loop_cells(&self, obstacle: (usize, usize)) -> Vec<Vec<char>> {
+        let walk = self.walk(Some(obstacle));
+        let Some(loop_start) = walk.loop_start else {
+            return vec![];
+        };
+
+        let states = &walk.states[loop_start..];
+        let rows = bounds(states.iter().map(|((row, _), _)| *row), self.grid.len());
+        let cols = bounds(states.iter().map(|((_, col), _)| *col), self.grid[0].len());
+
+        self.annotate(states, Some(obstacle))[rows]
+            .iter()
+            .map(|row| row[cols.clone()].to_vec())
+            .collect()
+    }
 
-        count
+    fn explain_loop(&self, obstacle: (usize, usize)) -> String {
+        let walk = self.walk(Some(obstacle));
+        let Some(loop_start) = walk.loop_start else {
+            return format!("Obstacle at {obstacle:?} does not create a loop.");
+        };
+
+        let states = &walk.states[loop_start..];
+        let turns = states
+            .windows(2)
+            .filter(|pair| pair[0].0 == pair[1].0)
+            .count()
+            // the turn back into the first state closes the loop
+            + usize::from(states[states.len() - 1].0 == states[0].0);
+
+        format!(
+            "Obstacle at {obstacle:?} creates a loop of {} states with {turns} turns, entered after {loop_start} states:\n{}",
+            states.len(),
+            to_text(&self.loop_cells(obstacle))
+        )
     }
 
     fn next_position(&self) -> Option<(usize, usize)> {
-        let (dx, dy) = self.current_dir.vector();
+        self.step(self.current_pos, self.current_dir)
+    }
+
+    fn step(&self, pos: (usize, usize), dir: Direction) -> Option<(usize, usize)> {
+        let (dx, dy) = dir.vector();
 
-        let x = self.current_pos.0 as isize + dx;
-        let y = self.current_pos.1 as isize + dy;
+        let x = pos.0 as isize + dx;
+        let y = pos.1 as isize + dy;
 
         if x >= 0 && y >= 0 && x < self.grid.len() as isize && y < self.grid[0].len() as isize {
             Some((x as usize, y as usize))
@@ -87,23 +328,154 @@ impl Grid {
         let mut visited_states = HashSet::from([(self.current_pos, self.current_dir)]);
 
         loop {
-            if let Some(next_pos) = self.next_position() {
-                match self.grid[next_pos.0][next_pos.1] {
-                    Cell::Obstacle => {
-                        self.turn_right();
-                    }
-                    Cell::Empty => {
-                        self.current_pos = next_pos;
-                        let state = (self.current_pos, self.current_dir);
-                        if visited_states.contains(&state) {
-                            return true;
-                        }
-                        visited_states.insert(state);
-                    }
+            let Some(next_pos) = self.next_position() else {
+                return false;
+            };
+            match self.grid[next_pos.0][next_pos.1] {
+                Cell::Obstacle => self.turn_right(),
+                Cell::Empty => self.current_pos = next_pos,
+            }
+
+            // NOTE: turns are states too, a guard boxed in on all four sides only ever turns.
+            if !visited_states.insert((self.current_pos, self.current_dir)) {
+                return true;
+            }
+        }
+    }
+}
+
+/// The states of the guard, one per step or turn. If the guard walks in a loop, the states from `loop_start` on
+/// repeat forever.
+struct Walk {
+    states: Vec<((usize, usize), Direction)>,
+    loop_start: Option<usize>,
+}
+
+/// The range of values, with one more on each side if it fits into `0..len`.
+fn bounds(values: impl Iterator<Item = usize> + Clone, len: usize) -> RangeInclusive<usize> {
+    let min = values.clone().min().unwrap_or(0);
+    let max = values.max().unwrap_or(0);
+    min.saturating_sub(1)..=(max + 1).min(len - 1)
+}
+
+fn to_text(cells: &[Vec<char>]) -> String {
+    cells
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A cell to place an obstacle on, with the guard's position and direction right before entering it for the first
+/// time. The walk only changes from there on, so loop checks start from that state.
+struct Candidate {
+    obstacle: (usize, usize),
+    pos: (usize, usize),
+    dir: Direction,
+}
+
+/// For every cell and direction, where the guard stops walking straight ahead: the cell in front of the next
+/// obstacle, or `None` if the guard walks off the map. Lets loop checks move from obstacle to obstacle.
+struct JumpTable {
+    cols: usize,
+    stops: Vec<[Option<(usize, usize)>; 4]>,
+}
+
+impl JumpTable {
+    fn new(grid: &Grid) -> Self {
+        let (rows, cols) = (grid.grid.len(), grid.grid[0].len());
+        let mut stops = vec![[None; 4]; rows * cols];
+
+        for dir in Direction::ALL {
+            let (dx, dy) = dir.vector();
+            // visit cells so the cell ahead of each cell is always done before it.
+            let row_order: Vec<usize> = if dx > 0 {
+                (0..rows).rev().collect()
+            } else {
+                (0..rows).collect()
+            };
+            let col_order: Vec<usize> = if dy > 0 {
+                (0..cols).rev().collect()
+            } else {
+                (0..cols).collect()
+            };
+
+            for &i in &row_order {
+                for &j in &col_order {
+                    stops[i * cols + j][dir as usize] = match grid.step((i, j), dir) {
+                        None => None,
+                        Some((ni, nj)) => match grid.grid[ni][nj] {
+                            Cell::Obstacle => Some((i, j)),
+                            Cell::Empty => stops[ni * cols + nj][dir as usize],
+                        },
+                    };
                 }
+            }
+        }
+
+        Self { cols, stops }
+    }
+
+    /// Where the guard stops walking straight ahead, with an additional obstacle placed on the map.
+    fn jump(
+        &self,
+        pos: (usize, usize),
+        dir: Direction,
+        obstacle: (usize, usize),
+    ) -> Option<(usize, usize)> {
+        let stop = self.stops[pos.0 * self.cols + pos.1][dir as usize];
+        let (dx, dy) = dir.vector();
+
+        // number of steps ahead to reach `target`, if it lies straight ahead at all.
+        let distance = |target: (usize, usize)| {
+            let di = target.0 as isize - pos.0 as isize;
+            let dj = target.1 as isize - pos.1 as isize;
+            let ahead = if dx == 0 {
+                di == 0 && dj * dy > 0
             } else {
-                return false;
+                dj == 0 && di * dx > 0
+            };
+            ahead.then(|| di.unsigned_abs() + dj.unsigned_abs())
+        };
+
+        let reach = stop.map(|stop| distance(stop).unwrap_or(0));
+
+        match distance(obstacle) {
+            Some(steps) if reach.is_none_or(|reach| steps <= reach) => {
+                let steps = (steps - 1) as isize;
+                Some((
+                    (pos.0 as isize + dx * steps) as usize,
+                    (pos.1 as isize + dy * steps) as usize,
+                ))
             }
+            _ => stop,
+        }
+    }
+
+    /// Whether the guard, starting at `pos` facing `dir`, walks in a loop once `obstacle` is placed.
+    /// States are only recorded when the guard turns. `seen` holds a stamp per state and has to be
+    /// passed a different `stamp` on every call.
+    fn is_loop(
+        &self,
+        mut pos: (usize, usize),
+        mut dir: Direction,
+        obstacle: (usize, usize),
+        seen: &mut [u32],
+        stamp: u32,
+    ) -> bool {
+        loop {
+            match self.jump(pos, dir, obstacle) {
+                None => return false,
+                Some(stop) => pos = stop,
+            }
+
+            let state = (pos.0 * self.cols + pos.1) * 4 + dir as usize;
+            if seen[state] == stamp {
+                return true;
+            }
+            seen[state] = stamp;
+
+            dir = dir.turn_right();
         }
     }
 }
@@ -156,6 +528,8 @@ enum Direction {
 }
 
 impl Direction {
+    const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
     const DIRECTION_VECTORS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
 
     fn vector(&self) -> (isize, isize) {
@@ -173,6 +547,15 @@ impl Direction {
         Ok(direction)
     }
 
+    fn to_char(self) -> char {
+        match self {
+            Self::Up => '^',
+            Self::Right => '>',
+            Self::Down => 'v',
+            Self::Left => '<',
+        }
+    }
+
     fn turn_right(&self) -> Self {
         match self {
             Self::Up => Self::Right,
@@ -204,4 +587,81 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(6));
     }
+
+    #[test]
+    fn test_part_two_naive() {
+        let result = part_two_naive(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, Some(6));
+    }
+
+    #[test]
+    fn test_jump() {
+        let grid: Grid = "..#..\n.....\n..^..\n.....".parse().unwrap();
+        let jumps = JumpTable::new(&grid);
+
+        assert_eq!(jumps.jump((2, 2), Direction::Up, (3, 3)), Some((1, 2)));
+        assert_eq!(jumps.jump((2, 2), Direction::Right, (3, 3)), None);
+        assert_eq!(jumps.jump((2, 0), Direction::Right, (2, 3)), Some((2, 2)));
+        assert_eq!(jumps.jump((1, 2), Direction::Up, (3, 3)), Some((1, 2)));
+    }
+
+    #[test]
+    fn test_annotate_loop() {
+        let grid: Grid = advent_of_code::template::read_file("examples", DAY)
+            .parse()
+            .unwrap();
+        let walk = grid.walk(Some((6, 3)));
+        assert_eq!(walk.loop_start, Some(0));
+
+        let expected = "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...";
+        assert_eq!(
+            to_text(&grid.annotate(&walk.states, Some((6, 3)))),
+            expected
+        );
+        assert_eq!(grid.loop_cells((6, 3)).len(), 8);
+        assert!(grid.loop_cells((0, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_annotate_u_turn() {
+        let grid: Grid = ".#.\n...\n...\n.^.".parse().unwrap();
+        let walk = grid.walk(Some((1, 2)));
+        assert_eq!(walk.loop_start, None);
+        assert_eq!(walk.states.len(), 7);
+        assert_eq!(
+            to_text(&grid.annotate(&walk.states, Some((1, 2)))),
+            ".#.\n.+O\n.|.\n.^."
+        );
+    }
+
+    #[test]
+    fn test_boxed_in() {
+        let mut grid: Grid = ".#.\n#^#\n.#.".parse().unwrap();
+        assert!(grid.check_for_loop());
+
+        let input = ".#.\n#^#\n...";
+        assert_eq!(part_two_naive(input), Some(1));
+        assert_eq!(part_two(input), Some(1));
+    }
+
+    #[test]
+    fn test_generate() {
+        let input = generate(42, 30);
+        assert_eq!(input, generate(42, 30));
+        assert_eq!(input.lines().count(), 30);
+        assert_eq!(part_two(&input), part_two_naive(&input));
+
+        let mut grid: Grid = generate(3, 60).parse().unwrap();
+        assert!(!grid.check_for_loop());
+    }
 }
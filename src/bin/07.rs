@@ -1,72 +1,288 @@
-advent_of_code::solution!(7);
+use advent_of_code::template::generator::Rng;
+
+advent_of_code::solution!(7, generate);
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let mut sum: u64 = 0;
+    total_calibration(input, &[Operator::Add, Operator::Mul])
+}
 
-    for line in input.lines() {
-        if let Some((goal, seq)) = parse_line(line) {
-            if is_valid_seq_1(&seq[1..], seq[0], goal) {
-                sum = sum.saturating_add(goal as u64);
-            }
+pub fn part_two(input: &str) -> Option<u64> {
+    total_calibration(input, &[Operator::Add, Operator::Mul, Operator::Concat])
+}
+
+/// Numbers of equations. Wide enough for any `i64` or `u64` in the input, so no equation has to be rejected,
+/// and intermediate results that do not fit are pruned by checked arithmetic.
+type Value = i128;
+
+/// Sum of the goals of all equations that can be solved with the given operators.
+/// `None` if the sum is negative or does not fit a `u64`.
+fn total_calibration(input: &str, operators: &[Operator]) -> Option<u64> {
+    let mut sum: Value = 0;
+
+    for equation in input.lines().filter_map(Equation::parse) {
+        if let Some(assignment) = equation.solve(operators) {
+            debug_assert_eq!(equation.evaluate(&assignment), Some(equation.goal));
+            advent_of_code::trace!("{}", equation.format(&assignment));
+            sum = sum.checked_add(equation.goal)?;
         }
     }
 
-    Some(sum)
+    u64::try_from(sum).ok()
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let mut sum: u64 = 0;
+/// Operators are evaluated left to right, without precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Mul,
+    /// Appends the digits of the right operand to the left one, e.g. `12 || 345 = 12345`.
+    Concat,
+    // NOTE: not used by the puzzle, supported by the solver nonetheless.
+    #[allow(dead_code)]
+    Sub,
+    #[allow(dead_code)]
+    Xor,
+}
+
+impl Operator {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Mul => "*",
+            Self::Concat => "||",
+            Self::Sub => "-",
+            Self::Xor => "^",
+        }
+    }
+
+    /// `None` if the result is not defined or does not fit.
+    fn apply(self, acc: Value, operand: Value) -> Option<Value> {
+        match self {
+            Self::Add => acc.checked_add(operand),
+            Self::Mul => acc.checked_mul(operand),
+            Self::Concat if acc >= 0 && operand >= 0 => match concat_shift(operand) {
+                Some(shift) => acc.checked_mul(shift)?.checked_add(operand),
+                None => (acc == 0).then_some(operand),
+            },
+            Self::Concat => None,
+            Self::Sub => acc.checked_sub(operand),
+            Self::Xor => Some(acc ^ operand),
+        }
+    }
 
-    for line in input.lines() {
-        if let Some((goal, seq)) = parse_line(line) {
-            if is_valid_seq_2(&seq[1..], seq[0], goal) {
-                sum = sum.saturating_add(goal as u64);
+    /// The left operand that gives `result` with `operand` on the right, if there is one.
+    /// This is where the backwards search prunes: `*` requires `result` to be divisible by `operand`,
+    /// `||` requires `result` to end in the digits of `operand`.
+    fn undo(self, result: Value, operand: Value) -> Option<Value> {
+        match self {
+            Self::Add => result.checked_sub(operand),
+            Self::Mul => {
+                // NOTE: a zero operand gives a zero result for any left operand, which has no single value to undo to.
+                // `solve_backwards` handles that case.
+                if operand == 0 || result.checked_rem(operand)? != 0 {
+                    return None;
+                }
+                result.checked_div(operand)
+            }
+            Self::Concat => {
+                if result < 0 || operand < 0 {
+                    return None;
+                }
+                match concat_shift(operand) {
+                    Some(shift) => (result % shift == operand).then_some(result / shift),
+                    // only a zero on the left keeps the result in range.
+                    None => (result == operand).then_some(0),
+                }
             }
+            Self::Sub => result.checked_add(operand),
+            Self::Xor => Some(result ^ operand),
         }
     }
 
-    Some(sum)
+    /// Whether results stay non-negative for non-negative operands.
+    fn keeps_non_negative(self) -> bool {
+        matches!(self, Self::Add | Self::Mul | Self::Concat | Self::Xor)
+    }
+}
+
+/// The power of ten that shifts a number left by the digits of `operand`, `None` if it does not fit.
+fn concat_shift(operand: Value) -> Option<Value> {
+    Value::from(10_u8).checked_pow(operand.checked_ilog10().unwrap_or(0) + 1)
+}
+
+/// A calibration equation: the goal and the operands, which are combined by operators in between them.
+struct Equation {
+    goal: Value,
+    operands: Vec<Value>,
 }
 
-fn parse_line(line: &str) -> Option<(i64, Vec<i64>)> {
-    let (goal_str, seq_str) = line.split_once(':')?;
-    let goal = goal_str.trim().parse().ok()?;
-    let seq = seq_str
-        .split_whitespace()
-        .map(|num| num.parse().ok())
-        .collect::<Option<Vec<i64>>>()?;
-    Some((goal, seq))
+impl Equation {
+    fn parse(line: &str) -> Option<Self> {
+        let (goal, operands) = line.split_once(':')?;
+        let goal = goal.trim().parse().ok()?;
+        let operands = operands
+            .split_whitespace()
+            .map(|num| num.parse().ok())
+            .collect::<Option<Vec<Value>>>()?;
+
+        (!operands.is_empty()).then_some(Self { goal, operands })
+    }
+
+    /// Operators to put between the operands to reach the goal, if there are any.
+    ///
+    /// Searches backwards from the goal: the last operator has to turn some value into the goal with the last operand,
+    /// [`Operator::undo`] finds that value and rules out most operators right away.
+    fn solve(&self, operators: &[Operator]) -> Option<Vec<Operator>> {
+        let non_negative = operators.iter().all(|op| op.keeps_non_negative())
+            && self.operands.iter().all(|&operand| operand >= 0);
+
+        let mut assignment = Vec::with_capacity(self.operands.len() - 1);
+        if !solve_backwards(
+            self.goal,
+            &self.operands,
+            operators,
+            non_negative,
+            &mut assignment,
+        ) {
+            return None;
+        }
+
+        assignment.reverse();
+        Some(assignment)
+    }
+
+    /// The result of putting the operators between the operands, `None` if it does not fit.
+    fn evaluate(&self, assignment: &[Operator]) -> Option<Value> {
+        self.operands[1..]
+            .iter()
+            .zip(assignment)
+            .try_fold(self.operands[0], |acc, (&operand, op)| {
+                op.apply(acc, operand)
+            })
+    }
+
+    /// E.g. `3267 = 81 * 40 + 27`.
+    fn format(&self, assignment: &[Operator]) -> String {
+        let mut output = format!("{} = {}", self.goal, self.operands[0]);
+        for (operand, op) in self.operands[1..].iter().zip(assignment) {
+            output.push_str(&format!(" {} {operand}", op.symbol()));
+        }
+        output
+    }
 }
 
-fn is_valid_seq_1(sequence: &[i64], acc: i64, goal: i64) -> bool {
-    match sequence {
-        [] => acc == goal,
-        [next, rest @ ..] => {
-            let add_result = acc + next;
-            let mul_result = acc * next;
+/// Pushes the operators found, from last to first, onto `assignment`.
+fn solve_backwards(
+    goal: Value,
+    operands: &[Value],
+    operators: &[Operator],
+    non_negative: bool,
+    assignment: &mut Vec<Operator>,
+) -> bool {
+    let [rest @ .., last] = operands else {
+        return false;
+    };
+    if rest.is_empty() {
+        return *last == goal;
+    }
+
+    for &op in operators {
+        // NOTE: `x * 0 = 0` for any `x`, so the operands before only have to evaluate to something.
+        if op == Operator::Mul && *last == 0 && goal == 0 {
+            let mut prefix = Vec::with_capacity(rest.len() - 1);
+            if evaluate_any(rest[0], &rest[1..], operators, &mut prefix) {
+                assignment.push(op);
+                assignment.extend(prefix.iter().rev());
+                return true;
+            }
+            continue;
+        }
 
-            (add_result <= goal && is_valid_seq_1(rest, add_result, goal))
-                || (mul_result <= goal && is_valid_seq_1(rest, mul_result, goal))
+        let Some(previous) = op.undo(goal, *last) else {
+            continue;
+        };
+        if non_negative && previous < 0 {
+            continue;
         }
+
+        assignment.push(op);
+        if solve_backwards(previous, rest, operators, non_negative, assignment) {
+            return true;
+        }
+        assignment.pop();
     }
+
+    false
 }
 
-fn is_valid_seq_2(sequence: &[i64], acc: i64, goal: i64) -> bool {
-    match sequence {
-        [] => acc == goal,
-        [next, rest @ ..] => {
-            let add_result = acc + next;
-            let mul_result = acc * next;
-            let concat_result = {
-                let next_len = next.checked_ilog10().unwrap_or(0) + 1;
-                acc * 10_i64.pow(next_len) + next
+/// Searches forwards for any operators that combine `acc` with the operands without overflowing.
+/// Pushes the operators found, from first to last, onto `assignment`.
+fn evaluate_any(
+    acc: Value,
+    operands: &[Value],
+    operators: &[Operator],
+    assignment: &mut Vec<Operator>,
+) -> bool {
+    let [next, rest @ ..] = operands else {
+        return true;
+    };
+
+    for &op in operators {
+        let Some(acc) = op.apply(acc, *next) else {
+            continue;
+        };
+
+        assignment.push(op);
+        if evaluate_any(acc, rest, operators, assignment) {
+            return true;
+        }
+        assignment.pop();
+    }
+
+    false
+}
+
+/// Generates `size` equations of 2 to 12 operands. About half of them can be solved with `+`, `*` and `||`.
+pub fn generate(seed: u64, size: usize) -> String {
+    // NOTE: keep goals well below `u64::MAX`, so totals of large inputs still fit.
+    const MAX_GOAL: i64 = 1_000_000_000_000_000;
+
+    let mut rng = Rng::new(seed);
+    let mut output = String::new();
+
+    for _ in 0..size {
+        let len = rng.range(2..=12);
+        let mut seq: Vec<i64> = vec![rng.range(1..=999) as i64];
+        let mut goal = seq[0];
+
+        while (seq.len() as u64) < len {
+            let next = rng.range(1..=999) as i64;
+            let result = match rng.range(0..=2) {
+                0 => goal.checked_add(next),
+                1 => goal.checked_mul(next),
+                _ => goal
+                    .checked_mul(10_i64.pow(next.ilog10() + 1))
+                    .and_then(|acc| acc.checked_add(next)),
             };
 
-            (add_result <= goal && is_valid_seq_2(rest, add_result, goal))
-                || (mul_result <= goal && is_valid_seq_2(rest, mul_result, goal))
-                || (concat_result <= goal && is_valid_seq_2(rest, concat_result, goal))
+            match result.filter(|result| *result <= MAX_GOAL) {
+                Some(result) => {
+                    goal = result;
+                    seq.push(next);
+                }
+                None => break,
+            }
         }
+
+        if rng.chance(0.5) {
+            goal += 1;
+        }
+
+        let seq: Vec<String> = seq.iter().map(ToString::to_string).collect();
+        output.push_str(&format!("{goal}: {}\n", seq.join(" ")));
     }
+
+    output
 }
 
 #[cfg(test)]
@@ -84,4 +300,159 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(11387));
     }
+
+    #[test]
+    fn test_solve() {
+        let equation = Equation::parse("3267: 81 40 27").unwrap();
+        let assignment = equation.solve(&[Operator::Add, Operator::Mul]).unwrap();
+        assert_eq!(equation.evaluate(&assignment), Some(3267));
+        assert_eq!(equation.format(&assignment), "3267 = 81 * 40 + 27");
+
+        let equation = Equation::parse("7290: 6 8 6 15").unwrap();
+        assert_eq!(equation.solve(&[Operator::Add, Operator::Mul]), None);
+        assert_eq!(
+            equation.solve(&[Operator::Add, Operator::Mul, Operator::Concat]),
+            Some(vec![Operator::Mul, Operator::Concat, Operator::Mul])
+        );
+
+        assert_eq!(Equation::parse("5: 5").unwrap().solve(&[]), Some(vec![]));
+        assert!(Equation::parse("5:").is_none());
+    }
+
+    #[test]
+    fn test_solve_other_operators() {
+        let operators = [Operator::Sub, Operator::Xor];
+
+        let equation = Equation::parse("-4: 3 5 2").unwrap();
+        assert_eq!(
+            equation.solve(&operators),
+            Some(vec![Operator::Sub, Operator::Sub])
+        );
+
+        let equation = Equation::parse("4: 3 5 2").unwrap();
+        let assignment = equation.solve(&operators).unwrap();
+        assert_eq!(equation.format(&assignment), "4 = 3 ^ 5 - 2");
+    }
+
+    #[test]
+    fn test_undo() {
+        for op in [
+            Operator::Add,
+            Operator::Mul,
+            Operator::Concat,
+            Operator::Sub,
+            Operator::Xor,
+        ] {
+            for (acc, operand) in [(12, 345), (0, 7), (9, 1), (100, 10)] {
+                let result = op.apply(acc, operand).unwrap();
+                assert_eq!(
+                    op.undo(result, operand),
+                    Some(acc),
+                    "{acc} {op:?} {operand}"
+                );
+            }
+        }
+        assert_eq!(Operator::Mul.undo(10, 3), None);
+        assert_eq!(Operator::Concat.undo(1234, 35), None);
+    }
+
+    #[test]
+    fn test_zero_operand() {
+        let solve = |line: &str, operators: &[Operator]| {
+            let equation = Equation::parse(line).unwrap();
+            let assignment = equation.solve(operators)?;
+            assert_eq!(equation.evaluate(&assignment), Some(equation.goal));
+            Some(equation.format(&assignment))
+        };
+
+        assert_eq!(
+            solve("0: 5 0", &[Operator::Mul]).as_deref(),
+            Some("0 = 5 * 0")
+        );
+        assert_eq!(
+            solve("0: 3 4 0", &[Operator::Add, Operator::Mul]).as_deref(),
+            Some("0 = 3 + 4 * 0")
+        );
+        assert_eq!(
+            solve("0: -1 2 0", &[Operator::Concat, Operator::Mul]).as_deref(),
+            Some("0 = -1 * 2 * 0")
+        );
+        assert_eq!(solve("1: 5 0", &[Operator::Mul]), None);
+
+        // the operands before the zero have to evaluate without overflowing.
+        let max = Value::MAX;
+        assert_eq!(solve(&format!("0: {max} 2 0"), &[Operator::Mul]), None);
+        assert_eq!(
+            solve(&format!("0: {max} 2 0"), &[Operator::Mul, Operator::Sub]),
+            Some(format!("0 = {max} - 2 * 0"))
+        );
+    }
+
+    #[test]
+    fn test_near_limits() {
+        let operators = [Operator::Add, Operator::Mul, Operator::Concat];
+        let solvable = |line: &str| {
+            let equation = Equation::parse(line).unwrap();
+            let assignment = equation.solve(&operators)?;
+            assert_eq!(equation.evaluate(&assignment), Some(equation.goal));
+            Some(equation.format(&assignment))
+        };
+
+        // i64::MAX
+        assert_eq!(
+            solvable("9223372036854775807: 922337203685477580 7").as_deref(),
+            Some("9223372036854775807 = 922337203685477580 || 7")
+        );
+        assert_eq!(
+            solvable("9223372036854775807: 9223372036854775806 1").as_deref(),
+            Some("9223372036854775807 = 9223372036854775806 + 1")
+        );
+        // beyond i64::MAX, up to u64::MAX = (2^32 - 1) * (2^32 + 1)
+        assert_eq!(
+            solvable("9223372036854775808: 9223372036854775807 1").as_deref(),
+            Some("9223372036854775808 = 9223372036854775807 + 1")
+        );
+        assert_eq!(
+            solvable("18446744073709551615: 4294967295 4294967297").as_deref(),
+            Some("18446744073709551615 = 4294967295 * 4294967297")
+        );
+        // operands that would wrap around in 64 bits do not produce false positives
+        assert_eq!(solvable("0: 4294967296 4294967296"), None);
+        assert_eq!(solvable("1: 18446744073709551615 2"), None);
+        assert_eq!(
+            solvable("18446744073709551614: 9223372036854775807 2 0"),
+            Some("18446744073709551614 = 9223372036854775807 * 2 + 0".into())
+        );
+
+        // intermediate results beyond `Value` are pruned instead of overflowing
+        let max = Value::MAX;
+        // with wrapping arithmetic, `max * 2` would be -2
+        assert_eq!(solvable(&format!("-2: {max} 2")), None);
+        assert_eq!(
+            solvable(&format!("{max}: {max} 0")).as_deref(),
+            Some(format!("{max} = {max} + 0").as_str())
+        );
+        assert_eq!(Operator::Mul.apply(max, 2), None);
+        assert_eq!(Operator::Concat.apply(max, 1), None);
+        assert_eq!(Operator::Concat.apply(0, max), Some(max));
+        assert_eq!(Operator::Concat.undo(max, max), Some(0));
+        assert_eq!(Operator::Mul.undo(Value::MIN, -1), None);
+    }
+
+    #[test]
+    fn test_total_overflow() {
+        let input = "18446744073709551615: 18446744073709551615\n1: 1\n";
+        assert_eq!(part_one(input), None);
+        assert_eq!(part_one(&input[..input.len() - 5]), Some(u64::MAX));
+        assert_eq!(part_one("-5: -5"), None);
+    }
+
+    #[test]
+    fn test_generate() {
+        let input = generate(42, 100);
+        assert_eq!(input, generate(42, 100));
+        assert_eq!(input.lines().count(), 100);
+        assert!(input.lines().all(|line| Equation::parse(line).is_some()));
+        assert!(part_two(&input) >= part_one(&input));
+    }
 }
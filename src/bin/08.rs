@@ -1,20 +1,44 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap},
     convert::Infallible,
+    fmt,
     ops::{Add, Sub},
     str::FromStr,
 };
 
+use advent_of_code::template::visualize::Frame;
+
 advent_of_code::solution!(8);
 
 pub fn part_one(input: &str) -> Option<u32> {
     let Ok(map) = input.parse::<Map>();
-    Some(compute_antinodes(&map, AntinodeMode::DistanceBased).len() as u32)
+    let antinodes = compute_antinodes(&map, ratio_antinodes(2));
+    show(&map, &antinodes);
+    Some(antinodes.len() as u32)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
     let Ok(map) = input.parse::<Map>();
-    Some(compute_antinodes(&map, AntinodeMode::Collinear).len() as u32)
+    let antinodes = compute_antinodes(&map, harmonic_antinodes(None));
+    show(&map, &antinodes);
+    Some(antinodes.len() as u32)
+}
+
+/// Trace the report and overlay, and emit the overlays of all frequencies as a visualizer frame.
+fn show(map: &Map, antinodes: &Antinodes) {
+    advent_of_code::trace!("{}", antinodes.report(map));
+    advent_of_code::trace!("{}", antinodes.render(map, None));
+    advent_of_code::visualize!(Frame::from_text(
+        &map.frequencies()
+            .into_iter()
+            .map(|frequency| format!(
+                "frequency {frequency}\n{}",
+                antinodes.render(map, Some(frequency))
+            ))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    )
+    .with_caption("antinodes per frequency"));
 }
 
 #[derive(Default)]
@@ -28,6 +52,13 @@ impl Map {
     fn is_within_bounds(&self, pos: Position) -> bool {
         (0..=self.rows).contains(&pos.0) && (0..=self.cols).contains(&pos.1)
     }
+
+    /// All frequencies, sorted.
+    fn frequencies(&self) -> Vec<char> {
+        let mut frequencies: Vec<char> = self.antennas.keys().copied().collect();
+        frequencies.sort_unstable();
+        frequencies
+    }
 }
 
 impl FromStr for Map {
@@ -56,9 +87,16 @@ impl FromStr for Map {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+/// `(row, column)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct Position(isize, isize);
 
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.0, self.1)
+    }
+}
+
 impl Add for Position {
     type Output = Self;
 
@@ -75,80 +113,196 @@ impl Sub for Position {
     }
 }
 
-enum AntinodeMode {
-    DistanceBased,
-    Collinear,
+/// A pair of antennas of the same frequency that produces an antinode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Source {
+    frequency: char,
+    antennas: (Position, Position),
 }
 
-fn compute_antinodes(map: &Map, mode: AntinodeMode) -> HashSet<Position> {
-    let mut antinode_positions = HashSet::new();
-    for antenna_positions in map.antennas.values() {
-        let candidates = match mode {
-            AntinodeMode::DistanceBased => antinode_candidates(antenna_positions, |a, b| {
-                distance_based_antinodes(a, b, map)
-            }),
-            AntinodeMode::Collinear => {
-                antinode_candidates(antenna_positions, |a, b| collinear_antinodes(a, b, map))
-            }
-        };
-        for candidate in candidates {
-            if map.is_within_bounds(candidate) {
-                antinode_positions.insert(candidate);
+/// Antinodes on the map, with every pair of antennas that produces them.
+struct Antinodes {
+    sources: BTreeMap<Position, Vec<Source>>,
+}
+
+/// Finds antinodes with a rule that gives the antinodes of a pair of antennas. Antinodes outside the map are ignored.
+/// Any closure can be used as a rule, see [`ratio_antinodes`] and [`harmonic_antinodes`].
+fn compute_antinodes(
+    map: &Map,
+    rule: impl Fn(Position, Position, &Map) -> Vec<Position>,
+) -> Antinodes {
+    let mut sources: BTreeMap<Position, Vec<Source>> = BTreeMap::new();
+
+    for frequency in map.frequencies() {
+        let candidates = antinode_candidates(&map.antennas[&frequency], |a, b| rule(a, b, map));
+
+        for (antinode, antennas) in candidates {
+            if map.is_within_bounds(antinode) {
+                sources.entry(antinode).or_default().push(Source {
+                    frequency,
+                    antennas,
+                });
             }
         }
     }
-    antinode_positions
+
+    Antinodes { sources }
 }
 
-fn antinode_candidates<F>(antenna_positions: &[Position], strategy: F) -> Vec<Position>
+/// Applies the strategy to every pair of antennas, each candidate comes with the pair that produced it.
+fn antinode_candidates<F>(
+    antenna_positions: &[Position],
+    strategy: F,
+) -> Vec<(Position, (Position, Position))>
 where
     F: Fn(Position, Position) -> Vec<Position>,
 {
     let mut candidates = Vec::new();
     for (i, &pos_a) in antenna_positions.iter().enumerate() {
         for &pos_b in antenna_positions.iter().skip(i + 1) {
-            candidates.extend(strategy(pos_a, pos_b));
+            candidates.extend(
+                strategy(pos_a, pos_b)
+                    .into_iter()
+                    .map(|candidate| (candidate, (pos_a, pos_b))),
+            );
         }
     }
     candidates
 }
 
-fn distance_based_antinodes(p1: Position, p2: Position, map: &Map) -> Vec<Position> {
-    vec![p1 + p1 - p2, p2 + p2 - p1]
-        .into_iter()
-        .filter(|&pos| map.is_within_bounds(pos))
-        .collect()
+/// Points in line with both antennas, beyond either of them, where one antenna is `ratio` times as far away as the
+/// other. Only grid points count, so a pair only has these if its distance is divisible by `ratio - 1`.
+/// `ratio_antinodes(2)` gives the antinodes of part one.
+fn ratio_antinodes(ratio: isize) -> impl Fn(Position, Position, &Map) -> Vec<Position> {
+    move |p1, p2, _| {
+        let delta = p1 - p2;
+        let divisor = ratio - 1;
+
+        if divisor <= 0 || delta.0 % divisor != 0 || delta.1 % divisor != 0 {
+            return vec![];
+        }
+
+        let step = Position(delta.0 / divisor, delta.1 / divisor);
+        vec![p1 + step, p2 - step]
+    }
 }
 
-fn collinear_antinodes(p1: Position, p2: Position, map: &Map) -> Vec<Position> {
-    let delta_x = p2.0 - p1.0;
-    let delta_y = p2.1 - p1.1;
+/// Grid points in line with both antennas, including the antennas themselves.
+/// With a limit, only points up to `limit` times the distance of the antennas beyond either of them,
+/// `harmonic_antinodes(None)` gives the antinodes of part two.
+fn harmonic_antinodes(limit: Option<isize>) -> impl Fn(Position, Position, &Map) -> Vec<Position> {
+    move |p1, p2, map| {
+        let delta = p2 - p1;
+        let gcd = gcd(delta.0.abs(), delta.1.abs());
+        let step = Position(delta.0 / gcd, delta.1 / gcd);
+
+        // points are `p1 + k * step`, `p2` is at `k = gcd`.
+        let point = |k: isize| Position(p1.0 + k * step.0, p1.1 + k * step.1);
+        let (first, last) = match limit {
+            Some(limit) => (-limit * gcd, gcd + limit * gcd),
+            None => (isize::MIN, isize::MAX),
+        };
 
-    let gcd = gcd(delta_x.abs(), delta_y.abs());
-    let step_x = delta_x / gcd;
-    let step_y = delta_y / gcd;
+        let mut positions: Vec<Position> = (0..=gcd).map(point).collect();
+        positions.extend(
+            (1..)
+                .map(|k| -k)
+                .take_while(|&k| k >= first)
+                .map(point)
+                .take_while(|&pos| map.is_within_bounds(pos)),
+        );
+        positions.extend(
+            (gcd + 1..)
+                .take_while(|&k| k <= last)
+                .map(point)
+                .take_while(|&pos| map.is_within_bounds(pos)),
+        );
 
-    let mut positions = Vec::new();
+        positions
+    }
+}
 
-    positions.extend(
-        (1..)
-            .map(|k| Position(p1.0 - k * step_x, p1.1 - k * step_y))
-            .take_while(|&pos| map.is_within_bounds(pos)),
-    );
+impl Antinodes {
+    fn len(&self) -> usize {
+        self.sources.len()
+    }
 
-    positions.extend(
-        (0..=gcd)
-            .map(|k| Position(p1.0 + k * step_x, p1.1 + k * step_y))
-            .filter(|&pos| map.is_within_bounds(pos)),
-    );
+    /// Antinodes per frequency and per pair of antennas, and antinodes shared by several frequencies.
+    fn report(&self, map: &Map) -> String {
+        let mut by_pair: BTreeMap<(char, (Position, Position)), Vec<Position>> = BTreeMap::new();
+        let mut by_frequency: BTreeMap<char, usize> = BTreeMap::new();
+        let mut shared = 0;
+
+        for (&antinode, sources) in &self.sources {
+            let mut frequencies: Vec<char> = sources.iter().map(|s| s.frequency).collect();
+            frequencies.dedup();
+            for &frequency in &frequencies {
+                *by_frequency.entry(frequency).or_default() += 1;
+            }
+            if frequencies.len() > 1 {
+                shared += 1;
+            }
+
+            for source in sources {
+                by_pair
+                    .entry((source.frequency, source.antennas))
+                    .or_default()
+                    .push(antinode);
+            }
+        }
 
-    positions.extend(
-        (gcd + 1..)
-            .map(|k| Position(p1.0 + k * step_x, p1.1 + k * step_y))
-            .take_while(|&pos| map.is_within_bounds(pos)),
-    );
+        let mut output = String::new();
+        for frequency in map.frequencies() {
+            let antennas = map.antennas[&frequency].len();
+            output.push_str(&format!(
+                "{frequency}: {antennas} antennas, {} pairs, {} antinodes\n",
+                antennas * antennas.saturating_sub(1) / 2,
+                by_frequency.get(&frequency).copied().unwrap_or_default()
+            ));
 
+            for (&(_, (a, b)), antinodes) in by_pair.iter().filter(|((f, _), _)| *f == frequency) {
+                output.push_str(&format!("  {a} {b} -> {}\n", join(antinodes)));
+            }
+        }
+
+        output.push_str(&format!(
+            "{} antinodes, {shared} of them produced by more than one frequency",
+            self.len()
+        ));
+        output
+    }
+
+    /// The map with antennas and antinodes marked as `#`, like the puzzle draws it.
+    /// With a frequency, only its antennas and the antinodes it produces are shown.
+    fn render(&self, map: &Map, frequency: Option<char>) -> String {
+        let mut grid = vec![vec!['.'; map.cols as usize + 1]; map.rows as usize + 1];
+
+        for (&antinode, sources) in &self.sources {
+            if frequency.is_none_or(|frequency| sources.iter().any(|s| s.frequency == frequency)) {
+                grid[antinode.0 as usize][antinode.1 as usize] = '#';
+            }
+        }
+        for (&antenna, positions) in &map.antennas {
+            if frequency.is_none_or(|frequency| frequency == antenna) {
+                for position in positions {
+                    grid[position.0 as usize][position.1 as usize] = antenna;
+                }
+            }
+        }
+
+        grid.iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn join(positions: &[Position]) -> String {
     positions
+        .iter()
+        .map(Position::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 fn gcd(a: isize, b: isize) -> isize {
@@ -174,4 +328,116 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(34));
     }
+
+    #[test]
+    fn test_render() {
+        let Ok(map) = advent_of_code::template::read_file("examples", DAY).parse::<Map>();
+        let antinodes = compute_antinodes(&map, ratio_antinodes(2));
+
+        assert_eq!(
+            antinodes.render(&map, None),
+            "......#....#\n\
+             ...#....0...\n\
+             ....#0....#.\n\
+             ..#....0....\n\
+             ....0....#..\n\
+             .#....A.....\n\
+             ...#........\n\
+             #......#....\n\
+             ........A...\n\
+             .........A..\n\
+             ..........#.\n\
+             ..........#."
+        );
+        assert_eq!(
+            antinodes.render(&map, Some('A')),
+            "............\n\
+             ...#........\n\
+             ....#.......\n\
+             ............\n\
+             ............\n\
+             ......A.....\n\
+             ............\n\
+             .......#....\n\
+             ........A...\n\
+             .........A..\n\
+             ..........#.\n\
+             ..........#."
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let Ok(map) = advent_of_code::template::read_file("examples", DAY).parse::<Map>();
+        let antinodes = compute_antinodes(&map, ratio_antinodes(2));
+
+        assert_eq!(
+            antinodes.sources[&Position(5, 6)],
+            vec![Source {
+                frequency: '0',
+                antennas: (Position(1, 8), Position(3, 7)),
+            }]
+        );
+        assert_eq!(
+            antinodes.sources[&Position(1, 3)],
+            vec![
+                Source {
+                    frequency: '0',
+                    antennas: (Position(2, 5), Position(3, 7)),
+                },
+                Source {
+                    frequency: 'A',
+                    antennas: (Position(5, 6), Position(9, 9)),
+                },
+            ]
+        );
+
+        let report = antinodes.report(&map);
+        assert!(report.starts_with("0: 4 antennas, 6 pairs, 10 antinodes\n"));
+        assert!(report.contains("A: 3 antennas, 3 pairs, 5 antinodes\n"));
+        assert!(report.contains("  (5, 6) (8, 8) -> (2, 4), (11, 10)\n"));
+        assert!(report.ends_with("14 antinodes, 1 of them produced by more than one frequency"));
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let Ok(map) = "\
+            .........\n\
+            .........\n\
+            .........\n\
+            ...a.....\n\
+            .........\n\
+            .....a...\n\
+            .........\n\
+            .........\n\
+            ........."
+            .parse::<Map>();
+        let positions = |antinodes: Antinodes| antinodes.sources.into_keys().collect::<Vec<_>>();
+
+        assert_eq!(
+            positions(compute_antinodes(&map, ratio_antinodes(3))),
+            vec![Position(2, 2), Position(6, 6)]
+        );
+        assert_eq!(
+            positions(compute_antinodes(&map, ratio_antinodes(4))),
+            vec![]
+        );
+
+        let midpoint =
+            |a: Position, b: Position, _: &Map| vec![Position((a.0 + b.0) / 2, (a.1 + b.1) / 2)];
+        assert_eq!(
+            positions(compute_antinodes(&map, midpoint)),
+            vec![Position(4, 4)]
+        );
+
+        assert_eq!(
+            compute_antinodes(&map, harmonic_antinodes(Some(0))).len(),
+            3
+        );
+        assert_eq!(
+            compute_antinodes(&map, harmonic_antinodes(Some(1))).len(),
+            7
+        );
+        assert_eq!(compute_antinodes(&map, harmonic_antinodes(None)).len(), 9);
+    }
 }
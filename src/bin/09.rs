@@ -1,6 +1,8 @@
 use std::{convert::Infallible, iter, str::FromStr};
 
-advent_of_code::solution!(9);
+use advent_of_code::template::generator::Rng;
+
+advent_of_code::solution!(9, generate);
 
 pub fn part_one(input: &str) -> Option<u64> {
     let mut disk: Disk = input.parse().ok()?;
@@ -114,6 +116,22 @@ impl FromStr for Disk {
     }
 }
 
+/// Generates a disk map of `size` files, each followed by up to 9 blocks of free space.
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut output = String::with_capacity(size * 2);
+
+    for i in 0..size {
+        output.push_str(&rng.range(1..=9).to_string());
+        if i + 1 < size {
+            output.push_str(&rng.range(0..=9).to_string());
+        }
+    }
+
+    output.push('\n');
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,4 +147,14 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(2858));
     }
+
+    #[test]
+    fn test_generate() {
+        let input = generate(42, 100);
+        assert_eq!(input, generate(42, 100));
+        let disk: Disk = input.parse().unwrap();
+        assert_eq!(disk.file_ranges.len(), 100);
+        assert!(part_one(&input).is_some());
+        assert!(part_two(&input).is_some());
+    }
 }
@@ -1,32 +1,41 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve, time};
+use advent_of_code::template::commands::{
+    all, diff_test, download, fuzz_test, read, scaffold, solve, status, stress, time,
+};
 use args::{parse, AppArguments};
 
+#[cfg(feature = "today")]
+use advent_of_code::template::commands::{scaffold::ScaffoldOptions, wait};
 #[cfg(feature = "today")]
 use advent_of_code::template::Day;
 #[cfg(feature = "today")]
 use std::process;
 
 mod args {
-    use advent_of_code::template::Day;
+    use advent_of_code::template::{
+        bench_config::BenchOptions,
+        commands::{scaffold::ScaffoldOptions, solve::SolveOptions},
+        diff_test, fuzz, generator, Day,
+    };
     use std::process;
 
     pub enum AppArguments {
         Download {
             day: Day,
+            force: bool,
         },
         Read {
             day: Day,
+            part: Option<u8>,
         },
         Scaffold {
             day: Day,
             download: bool,
             overwrite: bool,
+            options: ScaffoldOptions,
         },
         Solve {
             day: Day,
-            release: bool,
-            dhat: bool,
-            submit: Option<u8>,
+            options: SolveOptions,
         },
         All {
             release: bool,
@@ -35,9 +44,33 @@ mod args {
             all: bool,
             day: Option<Day>,
             store: bool,
+            alloc: bool,
+            instructions: bool,
+            bench: BenchOptions,
+            variant: Option<String>,
+        },
+        Status {
+            run: bool,
+            store: bool,
+        },
+        Stress {
+            day: Day,
+            size: usize,
+            seed: u64,
+            steps: u32,
+        },
+        DiffTest {
+            day: Day,
+            options: diff_test::Options,
+        },
+        FuzzTest {
+            day: Day,
+            options: fuzz::Options,
         },
         #[cfg(feature = "today")]
         Today,
+        #[cfg(feature = "today")]
+        Wait,
     }
 
     pub fn parse() -> Result<AppArguments, Box<dyn std::error::Error>> {
@@ -50,32 +83,108 @@ mod args {
             Some("time") => {
                 let all = args.contains("--all");
                 let store = args.contains("--store");
+                let alloc = args.contains("--alloc");
+                let instructions = args.contains("--instructions");
+                let bench = BenchOptions {
+                    profile: args.opt_value_from_str("--bench")?,
+                    target_time_ms: args.opt_value_from_str("--target-time")?,
+                    min_samples: args.opt_value_from_str("--min-samples")?,
+                    max_samples: args.opt_value_from_str("--max-samples")?,
+                    warmup: args.opt_value_from_str("--warmup")?,
+                };
+                let variant = args.opt_value_from_str("--variant")?;
 
                 AppArguments::Time {
                     all,
                     day: args.opt_free_from_str()?,
                     store,
+                    alloc,
+                    instructions,
+                    bench,
+                    variant,
                 }
             }
+            Some("status") => AppArguments::Status {
+                run: args.contains("--run"),
+                store: args.contains("--store"),
+            },
+            Some("stress") => AppArguments::Stress {
+                day: args.free_from_str()?,
+                size: args
+                    .opt_value_from_str("--size")?
+                    .unwrap_or(generator::DEFAULT_SIZE),
+                seed: args
+                    .opt_value_from_str("--seed")?
+                    .unwrap_or(generator::DEFAULT_SEED),
+                steps: args.opt_value_from_fn("--steps", parse_steps)?.unwrap_or(5),
+            },
+            Some("diff-test") => AppArguments::DiffTest {
+                day: args.free_from_str()?,
+                options: diff_test::Options {
+                    cases: args
+                        .opt_value_from_str("--cases")?
+                        .unwrap_or(diff_test::DEFAULT_CASES),
+                    seed: args
+                        .opt_value_from_str("--seed")?
+                        .unwrap_or(generator::DEFAULT_SEED),
+                    size: args
+                        .opt_value_from_str("--size")?
+                        .unwrap_or(diff_test::DEFAULT_SIZE),
+                },
+            },
+            Some("fuzz-test") => AppArguments::FuzzTest {
+                day: args.free_from_str()?,
+                options: fuzz::Options {
+                    iterations: args
+                        .opt_value_from_str("--iterations")?
+                        .unwrap_or(fuzz::DEFAULT_ITERATIONS),
+                    seed: args
+                        .opt_value_from_str("--seed")?
+                        .unwrap_or(generator::DEFAULT_SEED),
+                    timeout_ms: args
+                        .opt_value_from_str("--timeout")?
+                        .unwrap_or(fuzz::DEFAULT_TIMEOUT_MS),
+                },
+            },
             Some("download") => AppArguments::Download {
                 day: args.free_from_str()?,
+                force: args.contains("--force"),
             },
             Some("read") => AppArguments::Read {
                 day: args.free_from_str()?,
+                part: args.opt_value_from_fn("--part", parse_part)?,
             },
             Some("scaffold") => AppArguments::Scaffold {
                 day: args.free_from_str()?,
                 download: args.contains("--download"),
                 overwrite: args.contains("--overwrite"),
+                options: ScaffoldOptions {
+                    preset: args.opt_value_from_str("--preset")?,
+                    return_type: args.opt_value_from_str("--type")?,
+                    example_answers: parse_example_answers(
+                        args.opt_value_from_str("--example-answers")?,
+                    ),
+                },
             },
             Some("solve") => AppArguments::Solve {
                 day: args.free_from_str()?,
-                release: args.contains("--release"),
-                submit: args.opt_value_from_str("--submit")?,
-                dhat: args.contains("--dhat"),
+                options: SolveOptions {
+                    release: args.contains("--release"),
+                    submit: args.opt_value_from_str("--submit")?,
+                    dhat: args.contains("--dhat"),
+                    alloc: args.contains("--alloc"),
+                    profile: args.contains("--profile"),
+                    trace: args.contains("--trace"),
+                    visualize: args.contains("--visualize"),
+                    fps: args.opt_value_from_str("--fps")?,
+                    export: args.opt_value_from_str("--export")?,
+                    variant: args.opt_value_from_str("--variant")?,
+                },
             },
             #[cfg(feature = "today")]
             Some("today") => AppArguments::Today,
+            #[cfg(feature = "today")]
+            Some("wait") => AppArguments::Wait,
             Some(x) => {
                 eprintln!("Unknown command: {x}");
                 process::exit(1);
@@ -93,6 +202,36 @@ mod args {
 
         Ok(app_args)
     }
+
+    /// Parses a puzzle part, which is either `1` or `2`.
+    fn parse_part(value: &str) -> Result<u8, String> {
+        match value.parse() {
+            Ok(part @ (1 | 2)) => Ok(part),
+            _ => Err("expected 1 or 2".to_string()),
+        }
+    }
+
+    /// Parses the number of stress steps, sizes are halved per step so at most `usize::BITS` are meaningful.
+    fn parse_steps(value: &str) -> Result<u32, String> {
+        match value.parse() {
+            Ok(steps @ 1..=usize::BITS) => Ok(steps),
+            _ => Err(format!("expected a number between 1 and {}", usize::BITS)),
+        }
+    }
+
+    /// Parses comma-separated example answers, e.g. `143,123` or `,123`.
+    fn parse_example_answers(value: Option<String>) -> [Option<String>; 2] {
+        let mut answers = [None, None];
+        if let Some(value) = value {
+            for (answer, part) in answers.iter_mut().zip(value.split(',')) {
+                let part = part.trim();
+                if !part.is_empty() {
+                    *answer = Some(part.to_string());
+                }
+            }
+        }
+        answers
+    }
 }
 
 fn main() {
@@ -103,32 +242,53 @@ fn main() {
         }
         Ok(args) => match args {
             AppArguments::All { release } => all::handle(release),
-            AppArguments::Time { day, all, store } => time::handle(day, all, store),
-            AppArguments::Download { day } => download::handle(day),
-            AppArguments::Read { day } => read::handle(day),
+            AppArguments::Time {
+                day,
+                all,
+                store,
+                alloc,
+                instructions,
+                bench,
+                variant,
+            } => time::handle(
+                day,
+                all,
+                store,
+                alloc,
+                instructions,
+                &bench,
+                variant.as_deref(),
+            ),
+            AppArguments::Status { run, store } => status::handle(run, store),
+            AppArguments::Stress {
+                day,
+                size,
+                seed,
+                steps,
+            } => stress::handle(day, size, seed, steps),
+            AppArguments::DiffTest { day, options } => diff_test::handle(day, &options),
+            AppArguments::FuzzTest { day, options } => fuzz_test::handle(day, &options),
+            AppArguments::Download { day, force } => download::handle(day, force),
+            AppArguments::Read { day, part } => read::handle(day, part),
             AppArguments::Scaffold {
                 day,
                 download,
                 overwrite,
+                options,
             } => {
-                scaffold::handle(day, overwrite);
                 if download {
-                    download::handle(day);
+                    download::before_scaffold(day);
                 }
+                scaffold::handle(day, overwrite, &options);
             }
-            AppArguments::Solve {
-                day,
-                release,
-                dhat,
-                submit,
-            } => solve::handle(day, release, dhat, submit),
+            AppArguments::Solve { day, options } => solve::handle(day, &options),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
                     Some(day) => {
-                        scaffold::handle(day, false);
-                        download::handle(day);
-                        read::handle(day)
+                        download::before_scaffold(day);
+                        scaffold::handle(day, false, &ScaffoldOptions::default());
+                        read::handle(day, None)
                     }
                     None => {
                         eprintln!(
@@ -139,6 +299,8 @@ fn main() {
                     }
                 };
             }
+            #[cfg(feature = "today")]
+            AppArguments::Wait => wait::handle(),
         },
     };
 }
@@ -0,0 +1,190 @@
+/// A global allocator that counts heap allocations, used to report allocation statistics per part.
+/// Activated with the `alloc-count` feature, see the `solution!` macro.
+/// Both this and `dhat-heap` install a global allocator, if both features are enabled, DHAT takes priority.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering::Relaxed};
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+// NOTE: relative to the start of counting, frees of memory allocated before can make it negative.
+static CURRENT: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+
+/// Wraps the system allocator and counts allocations between [`start`] and [`stop`].
+/// Outside of that, it only forwards to the system allocator, so benchmarks are not skewed by the counting.
+pub struct CountingAlloc;
+
+#[allow(clippy::cast_possible_wrap)]
+fn track_alloc(size: usize) {
+    if !COUNTING.load(Relaxed) {
+        return;
+    }
+    ALLOCATIONS.fetch_add(1, Relaxed);
+    BYTES.fetch_add(size, Relaxed);
+    let current = CURRENT.fetch_add(size as isize, Relaxed) + size as isize;
+    PEAK.fetch_max(current, Relaxed);
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn track_dealloc(size: usize) {
+    if COUNTING.load(Relaxed) {
+        CURRENT.fetch_sub(size as isize, Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            track_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            track_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        track_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            // NOTE: a reallocation counts as a new allocation of `new_size` bytes.
+            track_dealloc(layout.size());
+            track_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Allocation statistics of a single run.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AllocStats {
+    /// Number of allocations (including reallocations).
+    pub allocations: usize,
+    /// Total number of bytes allocated.
+    pub bytes: usize,
+    /// Maximum number of bytes in use at the same time, not counting memory in use before the run.
+    pub peak_bytes: usize,
+}
+
+/// Reset the counters and start counting, memory currently in use is not counted towards the peak.
+pub fn start() {
+    ALLOCATIONS.store(0, Relaxed);
+    BYTES.store(0, Relaxed);
+    CURRENT.store(0, Relaxed);
+    PEAK.store(0, Relaxed);
+    COUNTING.store(true, Relaxed);
+}
+
+/// Stop counting and read the counters since the last [`start`].
+#[allow(clippy::cast_sign_loss)]
+pub fn stop() -> AllocStats {
+    COUNTING.store(false, Relaxed);
+    AllocStats {
+        allocations: ALLOCATIONS.load(Relaxed),
+        bytes: BYTES.load(Relaxed),
+        peak_bytes: PEAK.load(Relaxed).max(0) as usize,
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{value:.2} {}", UNITS[unit])
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn parse_bytes(s: &str) -> Option<usize> {
+    let (value, unit) = s.trim().split_once(' ')?;
+    let value: f64 = value.parse().ok()?;
+    let factor = match unit {
+        "B" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((value * factor).round() as usize)
+}
+
+impl Display for AllocStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} allocs · {} · peak {}",
+            self.allocations,
+            format_bytes(self.bytes),
+            format_bytes(self.peak_bytes)
+        )
+    }
+}
+
+impl AllocStats {
+    /// Parses the `Display` representation of allocation stats.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.split(" · ");
+        let allocations = parts.next()?.strip_suffix(" allocs")?.parse().ok()?;
+        let bytes = parse_bytes(parts.next()?)?;
+        let peak_bytes = parse_bytes(parts.next()?.strip_prefix("peak ")?)?;
+        Some(Self {
+            allocations,
+            bytes,
+            peak_bytes,
+        })
+    }
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{format_bytes, parse_bytes, AllocStats};
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(format_bytes(12), "12 B");
+        assert_eq!(format_bytes(2048), "2.00 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 + 512 * 1024), "3.50 MiB");
+    }
+
+    #[test]
+    fn parses_bytes() {
+        assert_eq!(parse_bytes("12 B"), Some(12));
+        assert_eq!(parse_bytes("2.00 KiB"), Some(2048));
+        assert_eq!(parse_bytes("3.50 MiB"), Some(3 * 1024 * 1024 + 512 * 1024));
+        assert_eq!(parse_bytes("3.50 XB"), None);
+    }
+
+    #[test]
+    fn roundtrips_stats() {
+        let stats = AllocStats {
+            allocations: 24,
+            bytes: 2048,
+            peak_bytes: 512,
+        };
+        assert_eq!(stats.to_string(), "24 allocs · 2.00 KiB · peak 512 B");
+        assert_eq!(AllocStats::parse(&stats.to_string()), Some(stats));
+    }
+}
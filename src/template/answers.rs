@@ -0,0 +1,132 @@
+use std::{collections::HashMap, fs, str::FromStr};
+use tinyjson::JsonValue;
+
+use crate::template::Day;
+
+static ANSWERS_FILE_PATH: &str = "./data/answers.json";
+
+/// Represents the recorded (accepted) answers for a single day.
+#[derive(Clone, Debug)]
+pub struct Answer {
+    pub day: Day,
+    pub part_1: Option<String>,
+    pub part_2: Option<String>,
+}
+
+/// Represents the recorded answers for a set of days.
+/// Can be deserialized from JSON.
+#[derive(Clone, Debug, Default)]
+pub struct Answers {
+    pub data: Vec<Answer>,
+}
+
+impl Answers {
+    /// Rehydrate answers from a JSON file. If not present, returns empty answers.
+    pub fn read_from_file() -> Self {
+        fs::read_to_string(ANSWERS_FILE_PATH)
+            .map_err(|x| x.to_string())
+            .and_then(Answers::try_from)
+            .unwrap_or_default()
+    }
+
+    /// Returns the recorded answer for one part of a day, if any.
+    pub fn get(&self, day: Day, part: u8) -> Option<&str> {
+        let answer = self.data.iter().find(|a| a.day == day)?;
+        match part {
+            1 => answer.part_1.as_deref(),
+            2 => answer.part_2.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl TryFrom<String> for Answers {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let json = JsonValue::from_str(&value).or(Err("not valid JSON file."))?;
+
+        let json_data = json
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("expected JSON document to be an object.")?
+            .get("data")
+            .ok_or("expected JSON document to have key `data`.")?
+            .get::<Vec<JsonValue>>()
+            .ok_or("expected `json.data` to be an array.")?;
+
+        Ok(Answers {
+            data: json_data
+                .iter()
+                .map(Answer::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryFrom<&JsonValue> for Answer {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected answer to be a JSON object.")?;
+
+        let day = json
+            .get("day")
+            .and_then(|v| v.get::<String>())
+            .and_then(|day| Day::from_str(day).ok())
+            .ok_or("Expected answer.day to be a Day struct.")?;
+
+        let part_1 = parse_part(json.get("part_1"))
+            .ok_or("Expected answer.part_1 to be null, string or number.")?;
+
+        let part_2 = parse_part(json.get("part_2"))
+            .ok_or("Expected answer.part_2 to be null, string or number.")?;
+
+        Ok(Answer {
+            day,
+            part_1,
+            part_2,
+        })
+    }
+}
+
+/// Answers may be stored as strings or as (integer) numbers. Missing keys are treated as `null`.
+fn parse_part(value: Option<&JsonValue>) -> Option<Option<String>> {
+    match value {
+        None | Some(JsonValue::Null) => Some(None),
+        Some(JsonValue::String(s)) => Some(Some(s.clone())),
+        #[allow(clippy::cast_possible_truncation)]
+        Some(JsonValue::Number(n)) => Some(Some((*n as i64).to_string())),
+        Some(_) => None,
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use crate::{day, template::answers::Answers};
+
+    #[test]
+    fn handles_json_answers() {
+        let json = r#"{ "data": [{ "day": "01", "part_1": "11", "part_2": 31 }, { "day": "02", "part_1": "2" }] }"#.to_string();
+        let answers = Answers::try_from(json).unwrap();
+        assert_eq!(answers.data.len(), 2);
+        assert_eq!(answers.get(day!(1), 1), Some("11"));
+        assert_eq!(answers.get(day!(1), 2), Some("31"));
+        assert_eq!(answers.get(day!(2), 1), Some("2"));
+        assert_eq!(answers.get(day!(2), 2), None);
+        assert_eq!(answers.get(day!(3), 1), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_for_malformed_answers() {
+        let json = r#"{ "data": [{ "day": "26", "part_1": "1" }] }"#.to_string();
+        Answers::try_from(json).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 /// Wrapper module around the "aoc-cli" command-line.
 use std::{
     fmt::Display,
+    fs,
     process::{Command, Output, Stdio},
 };
 
@@ -11,6 +12,7 @@ pub enum AocCommandError {
     CommandNotFound,
     CommandNotCallable,
     BadExitStatus(Output),
+    InvalidInput(&'static str),
 }
 
 impl Display for AocCommandError {
@@ -21,6 +23,9 @@ impl Display for AocCommandError {
             AocCommandError::BadExitStatus(_) => {
                 write!(f, "aoc-cli exited with a non-zero status.")
             }
+            AocCommandError::InvalidInput(reason) => {
+                write!(f, "downloaded input is invalid: {reason}")
+            }
         }
     }
 }
@@ -33,45 +38,120 @@ pub fn check() -> Result<(), AocCommandError> {
     Ok(())
 }
 
-pub fn read(day: Day) -> Result<Output, AocCommandError> {
-    let puzzle_path = get_puzzle_path(day);
-
-    let args = build_args(
-        "read",
-        &[
-            "--description-only".into(),
-            "--puzzle-file".into(),
-            puzzle_path,
-        ],
-        day,
-    );
-
-    call_aoc_cli(&args)
+/// Returns `true` if both a valid input and the full puzzle description are present for a day.
+pub fn is_cached(day: Day) -> bool {
+    is_cached_input(&get_input_path(day)) && is_cached_puzzle(&get_puzzle_path(day))
 }
 
-pub fn download(day: Day) -> Result<Output, AocCommandError> {
+/// Download the input and puzzle description for a day.
+///
+/// Inputs are cached: if a valid input is already present, only the puzzle description is fetched.
+/// Pass `force` to re-download the input regardless.
+/// Inputs are downloaded to a temporary file first and only moved into place if they look like a real input.
+pub fn download(day: Day, force: bool) -> Result<Output, AocCommandError> {
     let input_path = get_input_path(day);
     let puzzle_path = get_puzzle_path(day);
 
+    if !force && is_cached_input(&input_path) {
+        let output = download_puzzle(day)?;
+        println!("---");
+        println!("🎄 Input \"{}\" is cached, skipped download.", &input_path);
+        println!("🎄 Successfully wrote puzzle to \"{}\".", &puzzle_path);
+        return Ok(output);
+    }
+
+    let download_path = format!("{input_path}.download");
+
     let args = build_args(
         "download",
         &[
             "--overwrite".into(),
             "--input-file".into(),
-            input_path.to_string(),
+            download_path.to_string(),
             "--puzzle-file".into(),
             puzzle_path.to_string(),
         ],
         day,
     );
 
-    let output = call_aoc_cli(&args)?;
+    let output = call_aoc_cli(&args);
+
+    let validated = output.and_then(|output| {
+        let contents = fs::read_to_string(&download_path).unwrap_or_default();
+        validate_input(&contents).map_err(AocCommandError::InvalidInput)?;
+        fs::rename(&download_path, &input_path)
+            .map_err(|_| AocCommandError::InvalidInput("could not move input into place."))?;
+        Ok(output)
+    });
+
+    // NOTE: never leave a partial or invalid download behind.
+    let _ = fs::remove_file(&download_path);
+
+    let output = validated?;
     println!("---");
     println!("🎄 Successfully wrote input to \"{}\".", &input_path);
     println!("🎄 Successfully wrote puzzle to \"{}\".", &puzzle_path);
     Ok(output)
 }
 
+/// Download only the puzzle description for a day, overwriting a saved one.
+pub fn download_puzzle(day: Day) -> Result<Output, AocCommandError> {
+    let args = build_args(
+        "download",
+        &[
+            "--overwrite".into(),
+            "--puzzle-only".into(),
+            "--puzzle-file".into(),
+            get_puzzle_path(day),
+        ],
+        day,
+    );
+
+    call_aoc_cli(&args)
+}
+
+/// Checks that the contents of an input file look like a puzzle input, and not like an error page.
+pub fn validate_input(contents: &str) -> Result<(), &'static str> {
+    let trimmed = contents.trim_start();
+
+    if trimmed.is_empty() {
+        return Err("input is empty.");
+    }
+
+    let lowercase = trimmed
+        .chars()
+        .take(64)
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    if lowercase.starts_with("<!doctype") || lowercase.starts_with("<html") {
+        return Err("input is an HTML page. Is your session cookie valid?");
+    }
+
+    if trimmed.starts_with("Puzzle inputs differ by user") {
+        return Err("input requires a login. Is your session cookie valid?");
+    }
+
+    if trimmed.starts_with("Please don't repeatedly request") {
+        return Err("input is not available yet.");
+    }
+
+    if trimmed.starts_with("404 Not Found") {
+        return Err("input does not exist.");
+    }
+
+    Ok(())
+}
+
+fn is_cached_input(path: &str) -> bool {
+    fs::read_to_string(path).is_ok_and(|contents| validate_input(&contents).is_ok())
+}
+
+/// The description of part two is only included once part one is solved, so it is re-fetched until then.
+fn is_cached_puzzle(path: &str) -> bool {
+    fs::read_to_string(path).is_ok_and(|contents| contents.contains("--- Part Two ---"))
+}
+
 pub fn submit(day: Day, part: u8, result: &str) -> Result<Output, AocCommandError> {
     // workaround: the argument order is inverted for submit.
     let mut args = build_args("submit", &[], day);
@@ -84,11 +164,11 @@ fn get_input_path(day: Day) -> String {
     format!("data/inputs/{day}.txt")
 }
 
-fn get_puzzle_path(day: Day) -> String {
+pub fn get_puzzle_path(day: Day) -> String {
     format!("data/puzzles/{day}.md")
 }
 
-fn get_year() -> Option<u16> {
+pub fn get_year() -> Option<u16> {
     match std::env::var("AOC_YEAR") {
         Ok(x) => x.parse().ok().or(None),
         Err(_) => None,
@@ -123,3 +203,36 @@ fn call_aoc_cli(args: &[String]) -> Result<Output, AocCommandError> {
         Err(AocCommandError::BadExitStatus(output))
     }
 }
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::validate_input;
+
+    #[test]
+    fn accepts_puzzle_inputs() {
+        assert!(validate_input("3   4\n4   3\n").is_ok());
+        assert!(validate_input("<<>>\n").is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_inputs() {
+        assert!(validate_input("").is_err());
+        assert!(validate_input("\n  \n").is_err());
+    }
+
+    #[test]
+    fn rejects_error_pages() {
+        assert!(validate_input("<!DOCTYPE html>\n<html lang=\"en-us\">").is_err());
+        assert!(validate_input("<html><body>500</body></html>").is_err());
+        assert!(validate_input(
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n"
+        )
+        .is_err());
+        assert!(
+            validate_input("Please don't repeatedly request this endpoint before it unlocks!")
+                .is_err()
+        );
+        assert!(validate_input("404 Not Found\n").is_err());
+    }
+}
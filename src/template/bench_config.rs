@@ -0,0 +1,379 @@
+/// Settings for how long and how often `cargo time` benches a solution part.
+/// Settings are resolved from (in order of precedence) command-line flags, `./data/bench.json` and a built-in profile.
+/// A profile named on the command line replaces the config file entirely.
+use std::{collections::HashMap, fs, str::FromStr, time::Duration};
+use tinyjson::JsonValue;
+
+static BENCH_CONFIG_FILE_PATH: &str = "./data/bench.json";
+
+/// Built-in profiles, selectable via `--bench <profile>` or the `profile` key of the config file.
+const PROFILES: [(&str, BenchConfig); 3] = [
+    (
+        "default",
+        BenchConfig {
+            target_time: Duration::from_secs(1),
+            min_samples: 10,
+            max_samples: 10_000,
+            warmup: 0,
+        },
+    ),
+    (
+        "quick",
+        BenchConfig {
+            target_time: Duration::from_millis(100),
+            min_samples: 3,
+            max_samples: 1_000,
+            warmup: 0,
+        },
+    ),
+    (
+        "precise",
+        BenchConfig {
+            target_time: Duration::from_secs(5),
+            min_samples: 100,
+            max_samples: 100_000,
+            warmup: 10,
+        },
+    ),
+];
+
+/// Resolved bench settings for a run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchConfig {
+    /// Approximate wall time to spend benching a part, based on the duration of the first run.
+    pub target_time: Duration,
+    pub min_samples: u128,
+    pub max_samples: u128,
+    /// Untimed runs before sampling starts.
+    pub warmup: u128,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        PROFILES[0].1
+    }
+}
+
+/// Partial bench settings, as read from command-line flags or the config file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BenchOptions {
+    pub profile: Option<String>,
+    pub target_time_ms: Option<u64>,
+    pub min_samples: Option<u128>,
+    pub max_samples: Option<u128>,
+    pub warmup: Option<u128>,
+}
+
+impl BenchConfig {
+    /// Look up a built-in profile by name.
+    pub fn profile(name: &str) -> Result<Self, String> {
+        PROFILES
+            .iter()
+            .find(|(profile, _)| *profile == name)
+            .map(|(_, config)| *config)
+            .ok_or_else(|| {
+                let available: Vec<&str> = PROFILES.iter().map(|(p, _)| *p).collect();
+                format!(
+                    "unknown bench profile \"{name}\", available: {}",
+                    available.join(", ")
+                )
+            })
+    }
+
+    /// Resolve settings from command-line options and the config file, if present.
+    pub fn resolve(cli: &BenchOptions) -> Result<Self, String> {
+        let file = match fs::read_to_string(BENCH_CONFIG_FILE_PATH) {
+            Ok(contents) => BenchOptions::try_from(contents)
+                .map_err(|e| format!("{BENCH_CONFIG_FILE_PATH}: {e}"))?,
+            Err(_) => BenchOptions::default(),
+        };
+
+        Self::merge(&file, cli)
+    }
+
+    /// Explicit settings always take precedence over profiles, command-line flags over the config file.
+    /// If the command line names a profile, the config file is ignored, so `--bench quick` is never overridden by it.
+    fn merge(file: &BenchOptions, cli: &BenchOptions) -> Result<Self, String> {
+        let sources = if cli.profile.is_some() {
+            vec![cli]
+        } else {
+            vec![file, cli]
+        };
+
+        let profile = sources
+            .iter()
+            .rev()
+            .find_map(|options| options.profile.as_deref())
+            .unwrap_or(PROFILES[0].0);
+
+        let mut config = Self::profile(profile)?;
+
+        for options in sources {
+            if let Some(ms) = options.target_time_ms {
+                config.target_time = Duration::from_millis(ms);
+            }
+            if let Some(min_samples) = options.min_samples {
+                config.min_samples = min_samples;
+            }
+            if let Some(max_samples) = options.max_samples {
+                config.max_samples = max_samples;
+            }
+            if let Some(warmup) = options.warmup {
+                config.warmup = warmup;
+            }
+        }
+
+        if config.min_samples == 0 {
+            return Err("min samples must be at least 1.".into());
+        }
+
+        if config.min_samples > config.max_samples {
+            return Err(format!(
+                "min samples ({}) must not exceed max samples ({}).",
+                config.min_samples, config.max_samples
+            ));
+        }
+
+        Ok(config)
+    }
+
+    /// Number of samples to take for a part whose first run took `base_time`.
+    pub fn samples(&self, base_time: &Duration) -> u128 {
+        (self.target_time.as_nanos() / base_time.as_nanos().max(10))
+            .clamp(self.min_samples, self.max_samples)
+    }
+
+    /// Arguments that pass these settings to a solution binary.
+    pub fn to_args(&self) -> Vec<String> {
+        vec![
+            "--target-time".into(),
+            self.target_time.as_millis().to_string(),
+            "--min-samples".into(),
+            self.min_samples.to_string(),
+            "--max-samples".into(),
+            self.max_samples.to_string(),
+            "--warmup".into(),
+            self.warmup.to_string(),
+        ]
+    }
+
+    /// Read settings passed to a solution binary, see [`BenchConfig::to_args`]. Missing settings use the default profile.
+    pub fn from_args(args: &[String]) -> Self {
+        let value = |flag: &str| {
+            let pos = args.iter().position(|x| x == flag)?;
+            args.get(pos + 1)?.parse::<u128>().ok()
+        };
+
+        let mut config = Self::default();
+
+        if let Some(ms) = value("--target-time").and_then(|ms| u64::try_from(ms).ok()) {
+            config.target_time = Duration::from_millis(ms);
+        }
+        if let Some(min_samples) = value("--min-samples") {
+            config.min_samples = min_samples.max(1);
+        }
+        if let Some(max_samples) = value("--max-samples") {
+            config.max_samples = max_samples.max(config.min_samples);
+        }
+        if let Some(warmup) = value("--warmup") {
+            config.warmup = warmup;
+        }
+
+        config
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl TryFrom<String> for BenchOptions {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let json = JsonValue::from_str(&value).or(Err("not valid JSON file."))?;
+        BenchOptions::try_from(&json)
+    }
+}
+
+impl TryFrom<&JsonValue> for BenchOptions {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("expected bench settings to be an object.")?;
+
+        let profile = json
+            .get("profile")
+            .map(|v| {
+                v.get::<String>()
+                    .cloned()
+                    .ok_or("expected `json.profile` to be a string.")
+            })
+            .transpose()?;
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let number = |key: &str| {
+            json.get(key)
+                .map(|v| {
+                    v.get::<f64>()
+                        .filter(|n| **n >= 0.0)
+                        .map(|n| *n as u64)
+                        .ok_or(format!("expected `json.{key}` to be a positive number."))
+                })
+                .transpose()
+        };
+
+        Ok(BenchOptions {
+            profile,
+            target_time_ms: number("target_time_ms")?,
+            min_samples: number("min_samples")?.map(u128::from),
+            max_samples: number("max_samples")?.map(u128::from),
+            warmup: number("warmup")?.map(u128::from),
+        })
+    }
+}
+
+impl From<&BenchConfig> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &BenchConfig) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        map.insert(
+            "target_time_ms".into(),
+            JsonValue::Number(value.target_time.as_millis() as f64),
+        );
+        map.insert(
+            "min_samples".into(),
+            JsonValue::Number(value.min_samples as f64),
+        );
+        map.insert(
+            "max_samples".into(),
+            JsonValue::Number(value.max_samples as f64),
+        );
+        map.insert("warmup".into(), JsonValue::Number(value.warmup as f64));
+
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for BenchConfig {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let options = BenchOptions::try_from(value)?;
+
+        let (Some(target_time_ms), Some(min_samples), Some(max_samples), Some(warmup)) = (
+            options.target_time_ms,
+            options.min_samples,
+            options.max_samples,
+            options.warmup,
+        ) else {
+            return Err("expected bench settings to have all keys.".into());
+        };
+
+        Ok(BenchConfig {
+            target_time: Duration::from_millis(target_time_ms),
+            min_samples,
+            max_samples,
+            warmup,
+        })
+    }
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{BenchConfig, BenchOptions};
+
+    #[test]
+    fn resolves_default_profile() {
+        let config = BenchConfig::merge(&BenchOptions::default(), &BenchOptions::default());
+        assert_eq!(config, Ok(BenchConfig::default()));
+        assert_eq!(BenchConfig::default().target_time, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn prefers_cli_over_file() {
+        let file = BenchOptions {
+            profile: Some("precise".into()),
+            warmup: Some(2),
+            min_samples: Some(20),
+            ..Default::default()
+        };
+        let cli = BenchOptions {
+            profile: Some("quick".into()),
+            min_samples: Some(5),
+            ..Default::default()
+        };
+
+        let config = BenchConfig::merge(&file, &cli).unwrap();
+        assert_eq!(config.target_time, Duration::from_millis(100));
+        assert_eq!(config.min_samples, 5);
+        assert_eq!(config.max_samples, 1_000);
+        assert_eq!(config.warmup, 0);
+    }
+
+    #[test]
+    fn applies_file_without_cli_profile() {
+        let file = BenchOptions {
+            profile: Some("precise".into()),
+            warmup: Some(2),
+            min_samples: Some(20),
+            ..Default::default()
+        };
+        let cli = BenchOptions {
+            min_samples: Some(5),
+            ..Default::default()
+        };
+
+        let config = BenchConfig::merge(&file, &cli).unwrap();
+        assert_eq!(config.target_time, Duration::from_secs(5));
+        assert_eq!(config.min_samples, 5);
+        assert_eq!(config.max_samples, 100_000);
+        assert_eq!(config.warmup, 2);
+    }
+
+    #[test]
+    fn errors_for_invalid_settings() {
+        let unknown = BenchOptions {
+            profile: Some("foo".into()),
+            ..Default::default()
+        };
+        let err = BenchConfig::merge(&BenchOptions::default(), &unknown).unwrap_err();
+        assert!(err.contains("default, quick, precise"));
+
+        let inverted = BenchOptions {
+            min_samples: Some(100),
+            max_samples: Some(10),
+            ..Default::default()
+        };
+        assert!(BenchConfig::merge(&BenchOptions::default(), &inverted).is_err());
+    }
+
+    #[test]
+    fn computes_samples() {
+        let config = BenchConfig::default();
+        assert_eq!(config.samples(&Duration::from_millis(10)), 100);
+        assert_eq!(config.samples(&Duration::from_secs(2)), 10);
+        assert_eq!(config.samples(&Duration::from_nanos(1)), 10_000);
+    }
+
+    #[test]
+    fn roundtrips_args() {
+        let config = BenchConfig::profile("precise").unwrap();
+        assert_eq!(BenchConfig::from_args(&config.to_args()), config);
+    }
+
+    #[test]
+    fn parses_config_file() {
+        let json = r#"{ "profile": "quick", "warmup": 3 }"#.to_string();
+        let options = BenchOptions::try_from(json).unwrap();
+        assert_eq!(options.profile.as_deref(), Some("quick"));
+        assert_eq!(options.warmup, Some(3));
+        assert_eq!(options.min_samples, None);
+
+        assert!(BenchOptions::try_from(r#"{ "warmup": -1 }"#.to_string()).is_err());
+    }
+}
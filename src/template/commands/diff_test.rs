@@ -0,0 +1,35 @@
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+
+use crate::template::diff_test::Options;
+use crate::template::run_multi::get_path_for_bin;
+use crate::template::Day;
+
+/// Run all implementations of a day's parts against each other, see [`diff_test`](crate::template::diff_test).
+pub fn handle(day: Day, options: &Options) {
+    if !Path::new(&get_path_for_bin(day)).exists() {
+        eprintln!("Day {day} has not been scaffolded yet.");
+        process::exit(1);
+    }
+
+    let mut cmd_args = vec![
+        "run".to_string(),
+        "--quiet".to_string(),
+        "--release".to_string(),
+        "--bin".to_string(),
+        day.to_string(),
+        "--".to_string(),
+    ];
+    cmd_args.extend(options.to_args());
+
+    let status = Command::new("cargo")
+        .args(&cmd_args)
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .unwrap();
+
+    if !status.success() {
+        process::exit(status.code().unwrap_or(1));
+    }
+}
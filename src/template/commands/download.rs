@@ -1,14 +1,40 @@
 use crate::template::{aoc_cli, Day};
 use std::process;
 
-pub fn handle(day: Day) {
+pub fn handle(day: Day, force: bool) {
+    if !try_handle(day, force) {
+        process::exit(1);
+    }
+}
+
+/// Like [`handle`], but a failed download is only reported, so callers can go on without it.
+/// Returns `false` if the download failed.
+pub fn try_handle(day: Day, force: bool) -> bool {
+    if !force && aoc_cli::is_cached(day) {
+        println!("🎄 Input and puzzle for day {day} are cached, skipping download. Use `--force` to re-download.");
+        return true;
+    }
+
     if aoc_cli::check().is_err() {
         eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
-        process::exit(1);
+        return false;
     }
 
-    if let Err(e) = aoc_cli::download(day) {
+    if let Err(e) = aoc_cli::download(day, force) {
         eprintln!("failed to call aoc-cli: {e}");
-        process::exit(1);
-    };
+        return false;
+    }
+
+    true
+}
+
+/// Downloads before scaffolding, so the puzzle title is available to the template.
+/// A failed download does not stop the scaffold, the title then falls back to `Day NN`.
+pub fn before_scaffold(day: Day) {
+    if !try_handle(day, false) {
+        eprintln!(
+            "Scaffolding day {day} without the puzzle, its title falls back to \"Day {}\".",
+            day.into_inner()
+        );
+    }
 }
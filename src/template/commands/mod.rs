@@ -1,6 +1,12 @@
 pub mod all;
+pub mod diff_test;
 pub mod download;
+pub mod fuzz_test;
 pub mod read;
 pub mod scaffold;
 pub mod solve;
+pub mod status;
+pub mod stress;
 pub mod time;
+#[cfg(feature = "today")]
+pub mod wait;
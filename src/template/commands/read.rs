@@ -1,15 +1,66 @@
-use std::process;
+use std::{env, fs, fs::File, process, process::Command};
 
+use crate::template::markdown::{self, Section};
 use crate::template::{aoc_cli, Day};
 
-pub fn handle(day: Day) {
-    if aoc_cli::check().is_err() {
-        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
-        process::exit(1);
+const MAX_WIDTH: usize = 100;
+
+pub fn handle(day: Day, part: Option<u8>) {
+    let puzzle_path = aoc_cli::get_puzzle_path(day);
+    let section = part.map_or(Section::All, Section::Part);
+
+    let mut contents = fs::read_to_string(&puzzle_path).ok();
+
+    // fetch the puzzle if it was not saved yet, or if the requested part is missing from it.
+    let needs_fetch = contents
+        .as_deref()
+        .is_none_or(|c| markdown::render(c, MAX_WIDTH, section).is_none());
+
+    if needs_fetch {
+        if aoc_cli::check().is_ok() {
+            if let Err(e) = aoc_cli::download_puzzle(day) {
+                eprintln!("failed to call aoc-cli: {e}");
+            }
+            contents = fs::read_to_string(&puzzle_path).ok();
+        } else if contents.is_none() {
+            eprintln!("No puzzle saved at \"{puzzle_path}\" and command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
+            process::exit(1);
+        }
     }
 
-    if let Err(e) = aoc_cli::read(day) {
-        eprintln!("failed to call aoc-cli: {e}");
+    let Some(contents) = contents else {
+        eprintln!("Could not read puzzle from \"{puzzle_path}\".");
         process::exit(1);
     };
+
+    match markdown::render(&contents, terminal_width(), section) {
+        Some(rendered) => println!("{rendered}"),
+        None => {
+            eprintln!(
+                "Part {} of day {day} is not available yet.",
+                part.unwrap_or(1)
+            );
+            process::exit(1);
+        }
+    }
+}
+
+/// Determine the width of the terminal, limited to a readable line length.
+fn terminal_width() -> usize {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .or_else(stty_width)
+        .unwrap_or(80)
+        .min(MAX_WIDTH)
+}
+
+fn stty_width() -> Option<usize> {
+    let tty = File::open("/dev/tty").ok()?;
+    let output = Command::new("stty").arg("size").stdin(tty).output().ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
 }
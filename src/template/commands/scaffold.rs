@@ -1,14 +1,56 @@
 use std::{
-    fs::{File, OpenOptions},
-    io::Write,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Write},
     process,
 };
 
-use crate::template::Day;
+use crate::template::{aoc_cli, markdown, Day};
 
 const MODULE_TEMPLATE: &str =
     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/template.txt"));
 
+/// Built-in templates, selectable via `--preset`.
+const PRESETS: [(&str, &str); 4] = [
+    ("default", MODULE_TEMPLATE),
+    (
+        "grid",
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/templates/grid.txt"
+        )),
+    ),
+    (
+        "graph",
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/templates/graph.txt"
+        )),
+    ),
+    (
+        "parse-once",
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/templates/parse-once.txt"
+        )),
+    ),
+];
+
+/// User-defined templates live here as `<name>.txt` and take precedence over built-in presets.
+const USER_TEMPLATES_DIR: &str = "templates";
+
+const DEFAULT_RETURN_TYPE: &str = "u32";
+
+/// Options for generating a module from a template.
+#[derive(Debug, Default)]
+pub struct ScaffoldOptions {
+    /// Name of a user-defined template or built-in preset. Uses the default template if unset.
+    pub preset: Option<String>,
+    /// Return type of both parts. Defaults to `u32`.
+    pub return_type: Option<String>,
+    /// Expected results for the example of part one and part two.
+    pub example_answers: [Option<String>; 2],
+}
+
 fn safe_create_file(path: &str, overwrite: bool) -> Result<File, std::io::Error> {
     let mut file = OpenOptions::new();
     if overwrite {
@@ -19,19 +61,91 @@ fn safe_create_file(path: &str, overwrite: bool) -> Result<File, std::io::Error>
     file.truncate(true).write(true).open(path)
 }
 
-fn create_file(path: &str) -> Result<File, std::io::Error> {
-    OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(path)
+/// Creates an empty data file. Existing inputs and examples are never overwritten.
+fn create_data_file(path: &str, kind: &str) {
+    match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(_) => {
+            println!("Created empty {kind} file \"{path}\"");
+        }
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+            println!("Kept existing {kind} file \"{path}\"");
+        }
+        Err(e) => {
+            eprintln!("Failed to create {kind} file: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Resolves a template by name, looking at user-defined templates first.
+fn load_template(name: &str) -> Result<String, String> {
+    if let Ok(template) = fs::read_to_string(format!("{USER_TEMPLATES_DIR}/{name}.txt")) {
+        return Ok(template);
+    }
+
+    PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, template)| (*template).to_string())
+        .ok_or_else(|| {
+            let mut available: Vec<String> = PRESETS.iter().map(|(p, _)| (*p).into()).collect();
+            if let Ok(entries) = fs::read_dir(USER_TEMPLATES_DIR) {
+                available.extend(entries.filter_map(|e| {
+                    let path = e.ok()?.path();
+                    (path.extension()? == "txt")
+                        .then(|| path.file_stem()?.to_str().map(String::from))?
+                }));
+            }
+            format!(
+                "unknown preset \"{name}\", available: {}",
+                available.join(", ")
+            )
+        })
+}
+
+/// Substitutes all placeholders in a template.
+fn render_template(template: &str, day: Day, options: &ScaffoldOptions) -> String {
+    let year = aoc_cli::get_year().map_or_else(String::new, |y| y.to_string());
+
+    let title = fs::read_to_string(aoc_cli::get_puzzle_path(day))
+        .ok()
+        .and_then(|puzzle| markdown::title(&puzzle))
+        .unwrap_or_else(|| format!("Day {}", day.into_inner()));
+
+    let example_answer = |part: usize| {
+        options.example_answers[part]
+            .as_ref()
+            .map_or_else(|| "None".into(), |answer| format!("Some({answer})"))
+    };
+
+    template
+        .replace("%DAY_NUMBER%", &day.into_inner().to_string())
+        .replace("%YEAR%", &year)
+        .replace("%TITLE%", &title)
+        .replace(
+            "%RETURN_TYPE%",
+            options
+                .return_type
+                .as_deref()
+                .unwrap_or(DEFAULT_RETURN_TYPE),
+        )
+        .replace("%EXAMPLE_ANSWER_1%", &example_answer(0))
+        .replace("%EXAMPLE_ANSWER_2%", &example_answer(1))
 }
 
-pub fn handle(day: Day, overwrite: bool) {
+pub fn handle(day: Day, overwrite: bool, options: &ScaffoldOptions) {
     let input_path = format!("data/inputs/{day}.txt");
     let example_path = format!("data/examples/{day}.txt");
     let module_path = format!("src/bin/{day}.rs");
 
+    let template = match load_template(options.preset.as_deref().unwrap_or("default")) {
+        Ok(template) => template,
+        Err(e) => {
+            eprintln!("Failed to load template: {e}");
+            process::exit(1);
+        }
+    };
+
     let mut file = match safe_create_file(&module_path, overwrite) {
         Ok(file) => file,
         Err(e) => {
@@ -40,11 +154,7 @@ pub fn handle(day: Day, overwrite: bool) {
         }
     };
 
-    match file.write_all(
-        MODULE_TEMPLATE
-            .replace("%DAY_NUMBER%", &day.into_inner().to_string())
-            .as_bytes(),
-    ) {
+    match file.write_all(render_template(&template, day, options).as_bytes()) {
         Ok(()) => {
             println!("Created module file \"{}\"", &module_path);
         }
@@ -54,26 +164,52 @@ pub fn handle(day: Day, overwrite: bool) {
         }
     }
 
-    match create_file(&input_path) {
-        Ok(_) => {
-            println!("Created empty input file \"{}\"", &input_path);
-        }
-        Err(e) => {
-            eprintln!("Failed to create input file: {e}");
-            process::exit(1);
-        }
+    create_data_file(&input_path, "input");
+    create_data_file(&example_path, "example");
+
+    println!("---");
+    println!("🎄 Type `cargo solve {day}` to run your solution.");
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{load_template, render_template, ScaffoldOptions, PRESETS};
+    use crate::day;
+
+    #[test]
+    fn renders_default_template() {
+        let rendered = render_template(PRESETS[0].1, day!(7), &ScaffoldOptions::default());
+        assert!(rendered.starts_with("advent_of_code::solution!(7);"));
+        assert!(rendered.contains("pub fn part_one(input: &str) -> Option<u32> {"));
+        assert!(rendered.contains("assert_eq!(result, None);"));
+        assert!(!rendered.contains('%'));
     }
 
-    match create_file(&example_path) {
-        Ok(_) => {
-            println!("Created empty example file \"{}\"", &example_path);
-        }
-        Err(e) => {
-            eprintln!("Failed to create example file: {e}");
-            process::exit(1);
+    #[test]
+    fn renders_return_type_and_example_answers() {
+        let options = ScaffoldOptions {
+            preset: None,
+            return_type: Some("u64".into()),
+            example_answers: [Some("3749".into()), None],
+        };
+        let rendered = render_template(PRESETS[0].1, day!(7), &options);
+        assert!(rendered.contains("pub fn part_two(input: &str) -> Option<u64> {"));
+        assert!(rendered.contains("assert_eq!(result, Some(3749));"));
+        assert!(rendered.contains("assert_eq!(result, None);"));
+    }
+
+    #[test]
+    fn renders_all_presets() {
+        for (name, template) in PRESETS {
+            let rendered = render_template(template, day!(12), &ScaffoldOptions::default());
+            assert!(!rendered.contains('%'), "unknown placeholder in {name}");
         }
     }
 
-    println!("---");
-    println!("🎄 Type `cargo solve {day}` to run your solution.");
+    #[test]
+    fn errors_for_unknown_presets() {
+        let err = load_template("foo").unwrap_err();
+        assert!(err.contains("grid, graph, parse-once"));
+    }
 }
@@ -2,23 +2,83 @@ use std::process::{Command, Stdio};
 
 use crate::template::Day;
 
-pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
+/// Options for building and running a solution.
+#[derive(Debug, Default)]
+pub struct SolveOptions {
+    pub release: bool,
+    /// Profile heap allocations with DHAT.
+    pub dhat: bool,
+    /// Count heap allocations per part.
+    pub alloc: bool,
+    /// Write a flamegraph per part.
+    pub profile: bool,
+    /// Print output of `trace!`.
+    pub trace: bool,
+    /// Play back frames emitted with `visualize!`.
+    pub visualize: bool,
+    /// Frames per second, defaults to 10.
+    pub fps: Option<u32>,
+    /// Export frames as `cast` or `gif` instead of playing them.
+    pub export: Option<String>,
+    pub submit: Option<u8>,
+    /// Run an alternative implementation of a part instead, e.g. `naive` for `part_two_naive`.
+    pub variant: Option<String>,
+}
+
+pub fn handle(day: Day, options: &SolveOptions) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
 
-    if dhat {
+    if options.dhat {
         cmd_args.extend([
             "--profile".to_string(),
             "dhat".to_string(),
             "--features".to_string(),
             "dhat-heap".to_string(),
         ]);
-    } else if release {
+    } else if options.profile {
+        cmd_args.extend([
+            "--profile".to_string(),
+            "profiling".to_string(),
+            "--features".to_string(),
+            "profile".to_string(),
+        ]);
+    } else if options.alloc {
+        cmd_args.extend([
+            "--release".to_string(),
+            "--features".to_string(),
+            "alloc-count".to_string(),
+        ]);
+    } else if options.release {
         cmd_args.push("--release".to_string());
     }
 
+    if options.trace {
+        cmd_args.extend(["--features".to_string(), "trace".to_string()]);
+    }
+
+    if options.visualize {
+        cmd_args.extend(["--features".to_string(), "visualize".to_string()]);
+    }
+
     cmd_args.push("--".to_string());
 
-    if let Some(submit_part) = submit_part {
+    if let Some(fps) = options.fps {
+        cmd_args.extend(["--fps".to_string(), fps.to_string()]);
+    }
+
+    if let Some(export) = &options.export {
+        cmd_args.extend(["--export".to_string(), export.clone()]);
+    }
+
+    if options.profile {
+        cmd_args.push("--profile".to_string());
+    }
+
+    if let Some(variant) = &options.variant {
+        cmd_args.extend(["--variant".to_string(), variant.clone()]);
+    }
+
+    if let Some(submit_part) = options.submit {
         cmd_args.push("--submit".to_string());
         cmd_args.push(submit_part.to_string());
     }
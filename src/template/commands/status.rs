@@ -0,0 +1,95 @@
+use crate::template::status::{self, DayStatus};
+use crate::template::{readme_status, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+const COLUMNS: usize = 5;
+const CELL_WIDTH: usize = 11;
+
+pub fn handle(run: bool, store: bool) {
+    let statuses = status::collect(run);
+
+    print_calendar(&statuses);
+
+    if !run {
+        println!("{ANSI_ITALIC}Parts are derived from stored timings, append `--run` to check them against `data/answers.json`.{ANSI_RESET}");
+    }
+
+    if store {
+        println!();
+        match readme_status::update(&statuses) {
+            Ok(()) => {
+                println!("Stored updated status.");
+            }
+            Err(_) => {
+                eprintln!("Failed to store updated status.");
+            }
+        }
+    }
+}
+
+fn border(left: char, mid: char, right: char) -> String {
+    let segment = "─".repeat(CELL_WIDTH + 2);
+    let mut line = String::from(left);
+    line.push_str(&vec![segment; COLUMNS].join(&mid.to_string()));
+    line.push(right);
+    line
+}
+
+fn flag(value: bool, label: char) -> char {
+    if value {
+        label
+    } else {
+        '·'
+    }
+}
+
+fn cell_lines(status: &DayStatus) -> [String; 4] {
+    let runtime = status
+        .total_nanos
+        .map_or_else(|| "-".into(), |n| format!("{:.2}ms", n / 1_000_000_f64));
+
+    [
+        format!("Day {}", status.day),
+        format!(
+            "{} {} {}",
+            flag(status.scaffolded, 'S'),
+            flag(status.example, 'E'),
+            flag(status.input, 'I')
+        ),
+        format!(
+            "1 {}  2 {}",
+            status.parts[0].symbol(),
+            status.parts[1].symbol()
+        ),
+        runtime,
+    ]
+}
+
+fn print_calendar(statuses: &[DayStatus]) {
+    println!("{}", border('┌', '┬', '┐'));
+
+    for (row_idx, row) in statuses.chunks(COLUMNS).enumerate() {
+        if row_idx > 0 {
+            println!("{}", border('├', '┼', '┤'));
+        }
+
+        let cells: Vec<[String; 4]> = row.iter().map(cell_lines).collect();
+
+        for line_idx in 0..4 {
+            let mut line = String::from("│");
+            for (status, cell) in row.iter().zip(&cells) {
+                let content = &cell[line_idx];
+                let padding = " ".repeat(CELL_WIDTH.saturating_sub(content.chars().count()));
+                // NOTE: apply styling after padding, escape codes do not take up space.
+                if line_idx == 0 && status.is_started() {
+                    line.push_str(&format!(" {ANSI_BOLD}{content}{ANSI_RESET}{padding} │"));
+                } else {
+                    line.push_str(&format!(" {content}{padding} │"));
+                }
+            }
+            println!("{line}");
+        }
+    }
+
+    println!("{}", border('└', '┴', '┘'));
+    println!("S: scaffolded, E: example, I: input | ★: verified, ☆: solving, ✖: wrong answer");
+}
@@ -0,0 +1,169 @@
+use std::{path::Path, process, time::Duration};
+
+use crate::template::bench_config::BenchConfig;
+use crate::template::run_multi::{child_commands, get_path_for_bin};
+use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+/// Width of the bars in the runtime plot.
+const PLOT_WIDTH: usize = 40;
+
+/// Run both parts of a solution on generated inputs of increasing size and plot how runtime grows.
+/// Sizes double from `max_size / 2^(steps - 1)` up to `max_size`.
+pub fn handle(day: Day, max_size: usize, seed: u64, steps: u32) {
+    if !Path::new(&get_path_for_bin(day)).exists() {
+        eprintln!("Day {day} has not been scaffolded yet.");
+        process::exit(1);
+    }
+
+    // NOTE: every size is a separate bench, keep each one short.
+    let bench = BenchConfig::profile("quick").unwrap();
+
+    println!("{ANSI_BOLD}Day {day}{ANSI_RESET} {ANSI_ITALIC}(seed {seed}){ANSI_RESET}");
+    println!("{:>10} │ {:>12} │ {:>12}", "size", "part 1", "part 2");
+    println!("{:─>11}┼{:─>14}┼{:─>13}", "", "", "");
+
+    let mut samples: Vec<(usize, [Option<f64>; 2])> = vec![];
+
+    for size in sizes(max_size, steps) {
+        let output = match child_commands::run_generated(day, seed, size, &bench) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Failed to run day {day}: {e:?}");
+                process::exit(1);
+            }
+        };
+
+        let nanos = child_commands::parse_part_nanos(&output);
+        if nanos.iter().all(Option::is_none) {
+            eprintln!("Could not time day {day} on a generated input of size {size}.");
+            process::exit(1);
+        }
+
+        println!(
+            "{size:>10} │ {:>12} │ {:>12}",
+            format_nanos(nanos[0]),
+            format_nanos(nanos[1])
+        );
+        samples.push((size, nanos));
+    }
+
+    for part in 0..2 {
+        let points: Vec<(usize, f64)> = samples
+            .iter()
+            .filter_map(|(size, nanos)| Some((*size, nanos[part]?)))
+            .collect();
+
+        if points.is_empty() {
+            continue;
+        }
+
+        println!();
+        print_plot(part + 1, &points);
+    }
+}
+
+fn print_plot(part: usize, points: &[(usize, f64)]) {
+    let growth = growth_exponent(points)
+        .map(|exp| format!(" {ANSI_ITALIC}≈ O(n^{exp:.2}){ANSI_RESET}"))
+        .unwrap_or_default();
+    println!("{ANSI_BOLD}Part {part}{ANSI_RESET}{growth}");
+
+    let max = points.iter().map(|(_, nanos)| *nanos).fold(0.0, f64::max);
+    for (size, nanos) in points {
+        println!(
+            "{size:>10} │{:<PLOT_WIDTH$} {}",
+            bar(*nanos, max, PLOT_WIDTH),
+            format_nanos(Some(*nanos))
+        );
+    }
+}
+
+/// Input sizes to run, doubling up to `max_size`.
+fn sizes(max_size: usize, steps: u32) -> Vec<usize> {
+    let mut sizes: Vec<usize> = (0..steps.max(1))
+        .rev()
+        .map(|step| (max_size >> step).max(1))
+        .collect();
+    sizes.dedup();
+    sizes
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn bar(value: f64, max: f64, width: usize) -> String {
+    if max <= 0.0 {
+        return String::new();
+    }
+    let len = ((value / max) * width as f64).round() as usize;
+    "█".repeat(len.clamp(1, width))
+}
+
+/// Slope of a least-squares fit of `log(time)` over `log(size)`, i.e. `k` in `O(n^k)`.
+/// `None` if there are less than two distinct sizes.
+#[allow(clippy::cast_precision_loss)]
+fn growth_exponent(points: &[(usize, f64)]) -> Option<f64> {
+    let logs: Vec<(f64, f64)> = points
+        .iter()
+        .filter(|(size, nanos)| *size > 0 && *nanos > 0.0)
+        .map(|(size, nanos)| ((*size as f64).ln(), nanos.ln()))
+        .collect();
+
+    if logs.len() < 2 {
+        return None;
+    }
+
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let var_x: f64 = logs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if var_x == 0.0 {
+        return None;
+    }
+
+    let cov: f64 = logs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+
+    Some(cov / var_x)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn format_nanos(nanos: Option<f64>) -> String {
+    nanos.map_or_else(
+        || "-".into(),
+        |nanos| format!("{:.1?}", Duration::from_nanos(nanos as u64)),
+    )
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{bar, growth_exponent, sizes};
+
+    #[test]
+    fn doubles_sizes() {
+        assert_eq!(sizes(1000, 4), vec![125, 250, 500, 1000]);
+        assert_eq!(sizes(4, 5), vec![1, 2, 4]);
+        assert_eq!(sizes(10, 0), vec![10]);
+    }
+
+    #[test]
+    fn scales_bars() {
+        assert_eq!(bar(10.0, 10.0, 4), "████");
+        assert_eq!(bar(5.0, 10.0, 4), "██");
+        assert_eq!(bar(0.0, 10.0, 4), "█");
+    }
+
+    #[test]
+    fn estimates_growth() {
+        let linear = [(100, 1000.0), (200, 2000.0), (400, 4000.0)];
+        assert!((growth_exponent(&linear).unwrap() - 1.0).abs() < 1e-9);
+
+        let quadratic = [(100, 1.0), (200, 4.0), (400, 16.0)];
+        assert!((growth_exponent(&quadratic).unwrap() - 2.0).abs() < 1e-9);
+
+        assert!(growth_exponent(&[(100, 1.0)]).is_none());
+    }
+}
@@ -1,10 +1,32 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, process};
 
+use crate::template::bench_config::{BenchConfig, BenchOptions};
 use crate::template::run_multi::run_multi;
 use crate::template::timings::Timings;
 use crate::template::{all_days, readme_benchmarks, Day};
 
-pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
+pub fn handle(
+    day: Option<Day>,
+    run_all: bool,
+    store: bool,
+    count_allocs: bool,
+    count_instructions: bool,
+    bench_options: &BenchOptions,
+    variant: Option<&str>,
+) {
+    let bench = match BenchConfig::resolve(bench_options) {
+        Ok(bench) => bench,
+        Err(e) => {
+            eprintln!("Invalid bench settings: {e}");
+            process::exit(1);
+        }
+    };
+
+    if variant.is_some() && day.is_none() {
+        eprintln!("`--variant` can only be used together with a day, e.g. `cargo time 6 --variant naive`.");
+        process::exit(1);
+    }
+
     let stored_timings = Timings::read_from_file();
 
     let days_to_run = day.map_or_else(
@@ -21,7 +43,16 @@ pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
         |day| HashSet::from([day]),
     );
 
-    let timings = run_multi(&days_to_run, true, true).unwrap();
+    let timings = run_multi(
+        &days_to_run,
+        true,
+        true,
+        count_allocs,
+        count_instructions,
+        &bench,
+        variant,
+    )
+    .unwrap();
 
     if store {
         let merged_timings = stored_timings.merge(&timings);
@@ -0,0 +1,147 @@
+use std::{
+    io::{stdout, Write},
+    path::Path,
+    process,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, Utc};
+
+use crate::template::commands::{download, read, scaffold, scaffold::ScaffoldOptions};
+use crate::template::readme_benchmarks::get_path_for_bin;
+use crate::template::{Clock, Day, SystemClock};
+
+/// Time to wait after the unlock before downloading, so the server has caught up.
+const UNLOCK_GRACE: Duration = Duration::from_secs(2);
+
+pub fn handle() {
+    let clock = SystemClock;
+
+    let Some((day, unlock)) = Day::next_unlock(clock.now()) else {
+        eprintln!("Could not determine the next unlock time.");
+        process::exit(1);
+    };
+
+    println!(
+        "🎄 Day {day} unlocks at {}.",
+        unlock.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+    );
+
+    wait_until(&clock, unlock, |remaining| {
+        print!("\r⏳ {}  ", format_remaining(remaining));
+        let _ = stdout().flush();
+    });
+
+    clock.sleep(UNLOCK_GRACE);
+    println!("\r🔔 Day {day} is unlocked!      ");
+    println!("---");
+
+    download::before_scaffold(day);
+
+    // NOTE: a day may have been scaffolded ahead of time, keep the existing module in that case.
+    if !Path::new(&get_path_for_bin(day)).exists() {
+        scaffold::handle(day, false, &ScaffoldOptions::default());
+    }
+
+    read::handle(day, None);
+}
+
+/// Blocks until `target` is reached, calling `on_tick` with the remaining time about once a second.
+pub fn wait_until(clock: &impl Clock, target: DateTime<Utc>, on_tick: impl Fn(Duration)) {
+    loop {
+        let Ok(remaining) = (target - clock.now()).to_std() else {
+            return;
+        };
+
+        if remaining.is_zero() {
+            return;
+        }
+
+        on_tick(remaining);
+        clock.sleep(remaining.min(Duration::from_secs(1)));
+    }
+}
+
+fn format_remaining(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    let (days, hours, minutes, seconds) = (
+        secs / 86_400,
+        (secs % 86_400) / 3600,
+        (secs % 3600) / 60,
+        secs % 60,
+    );
+
+    if days > 0 {
+        format!("{days}d {hours:02}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{hours:02}:{minutes:02}:{seconds:02}")
+    }
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, time::Duration};
+
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::{format_remaining, wait_until};
+    use crate::template::Clock;
+
+    /// A clock that only advances when slept on.
+    struct ManualClock {
+        now: Cell<DateTime<Utc>>,
+        sleeps: Cell<u32>,
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.now.get()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.sleeps.set(self.sleeps.get() + 1);
+            self.now
+                .set(self.now.get() + chrono::Duration::from_std(duration).unwrap());
+        }
+    }
+
+    fn clock_at(now: DateTime<Utc>) -> ManualClock {
+        ManualClock {
+            now: Cell::new(now),
+            sleeps: Cell::new(0),
+        }
+    }
+
+    #[test]
+    fn waits_until_target() {
+        let start = Utc.with_ymd_and_hms(2024, 12, 5, 4, 59, 57).unwrap();
+        let target = Utc.with_ymd_and_hms(2024, 12, 5, 5, 0, 0).unwrap();
+        let clock = clock_at(start);
+        let ticks = Cell::new(0);
+
+        wait_until(&clock, target, |_| ticks.set(ticks.get() + 1));
+
+        assert_eq!(clock.now(), target);
+        assert_eq!(ticks.get(), 3);
+        assert_eq!(clock.sleeps.get(), 3);
+    }
+
+    #[test]
+    fn returns_immediately_if_target_passed() {
+        let start = Utc.with_ymd_and_hms(2024, 12, 5, 5, 0, 1).unwrap();
+        let target = Utc.with_ymd_and_hms(2024, 12, 5, 5, 0, 0).unwrap();
+        let clock = clock_at(start);
+
+        wait_until(&clock, target, |_| panic!("should not tick"));
+
+        assert_eq!(clock.sleeps.get(), 0);
+    }
+
+    #[test]
+    fn formats_remaining_time() {
+        assert_eq!(format_remaining(Duration::from_secs(59)), "00:00:59");
+        assert_eq!(format_remaining(Duration::from_secs(3_661)), "01:01:01");
+        assert_eq!(format_remaining(Duration::from_secs(90_061)), "1d 01:01:01");
+    }
+}
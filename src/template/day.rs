@@ -3,7 +3,9 @@ use std::fmt::Display;
 use std::str::FromStr;
 
 #[cfg(feature = "today")]
-use chrono::{Datelike, FixedOffset, Utc};
+use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Utc};
+#[cfg(feature = "today")]
+use std::time::Duration;
 
 #[cfg(feature = "today")]
 const SERVER_UTC_OFFSET: i32 = -5;
@@ -47,14 +49,68 @@ impl Day {
 impl Day {
     /// Returns the current day if it's between the 1st and the 25th of december, `None` otherwise.
     pub fn today() -> Option<Self> {
-        let offset = FixedOffset::east_opt(SERVER_UTC_OFFSET * 3600)?;
-        let today = Utc::now().with_timezone(&offset);
+        Self::today_at(SystemClock.now())
+    }
+
+    /// Returns the day that is unlocked at `now` if it's between the 1st and the 25th of december, `None` otherwise.
+    pub fn today_at(now: DateTime<Utc>) -> Option<Self> {
+        let today = now.with_timezone(&server_offset()?);
         if today.month() == 12 && today.day() <= 25 {
             Self::new(u8::try_from(today.day()).ok()?)
         } else {
             None
         }
     }
+
+    /// Returns the time at which the puzzle for this day unlocks in the given year.
+    pub fn unlock_time(self, year: i32) -> Option<DateTime<Utc>> {
+        server_offset()?
+            .with_ymd_and_hms(year, 12, u32::from(self.0), 0, 0, 0)
+            .single()
+            .map(|t| t.with_timezone(&Utc))
+    }
+
+    /// Returns the next day to unlock after `now`, together with its unlock time.
+    pub fn next_unlock(now: DateTime<Utc>) -> Option<(Self, DateTime<Utc>)> {
+        let server_now = now.with_timezone(&server_offset()?);
+        let year = server_now.year();
+
+        let (day, year) = match Self::today_at(now) {
+            Some(today) if today < 25 => (Self(today.0 + 1), year),
+            Some(_) => (Self(1), year + 1),
+            None if server_now.month() == 12 => (Self(1), year + 1),
+            None => (Self(1), year),
+        };
+
+        Some((day, day.unlock_time(year)?))
+    }
+}
+
+#[cfg(feature = "today")]
+fn server_offset() -> Option<FixedOffset> {
+    FixedOffset::east_opt(SERVER_UTC_OFFSET * 3600)
+}
+
+/// Source of the current time. Allows to test time-based behavior without waiting for real time.
+#[cfg(feature = "today")]
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+    fn sleep(&self, duration: Duration);
+}
+
+/// A [`Clock`] backed by the system time.
+#[cfg(feature = "today")]
+pub struct SystemClock;
+
+#[cfg(feature = "today")]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
 }
 
 impl Display for Day {
@@ -188,6 +244,53 @@ mod tests {
         assert_eq!(iter.next(), Some(Day(25)));
         assert_eq!(iter.next(), None);
     }
+
+    #[cfg(feature = "today")]
+    mod unlock {
+        use chrono::{TimeZone, Utc};
+
+        use super::Day;
+
+        #[test]
+        fn handles_today() {
+            // 2024-12-05 04:59 UTC is still the 4th on the server.
+            let now = Utc.with_ymd_and_hms(2024, 12, 5, 4, 59, 0).unwrap();
+            assert_eq!(Day::today_at(now), Some(Day(4)));
+            let now = Utc.with_ymd_and_hms(2024, 12, 5, 5, 0, 0).unwrap();
+            assert_eq!(Day::today_at(now), Some(Day(5)));
+            let now = Utc.with_ymd_and_hms(2024, 12, 27, 5, 0, 0).unwrap();
+            assert_eq!(Day::today_at(now), None);
+        }
+
+        #[test]
+        fn handles_next_unlock_during_advent() {
+            let now = Utc.with_ymd_and_hms(2024, 12, 5, 12, 0, 0).unwrap();
+            let (day, unlock) = Day::next_unlock(now).unwrap();
+            assert_eq!(day, Day(6));
+            assert_eq!(unlock, Utc.with_ymd_and_hms(2024, 12, 6, 5, 0, 0).unwrap());
+        }
+
+        #[test]
+        fn handles_next_unlock_before_advent() {
+            let now = Utc.with_ymd_and_hms(2024, 11, 30, 23, 0, 0).unwrap();
+            let (day, unlock) = Day::next_unlock(now).unwrap();
+            assert_eq!(day, Day(1));
+            assert_eq!(unlock, Utc.with_ymd_and_hms(2024, 12, 1, 5, 0, 0).unwrap());
+        }
+
+        #[test]
+        fn handles_next_unlock_after_advent() {
+            let now = Utc.with_ymd_and_hms(2024, 12, 25, 12, 0, 0).unwrap();
+            let (day, unlock) = Day::next_unlock(now).unwrap();
+            assert_eq!(day, Day(1));
+            assert_eq!(unlock, Utc.with_ymd_and_hms(2025, 12, 1, 5, 0, 0).unwrap());
+
+            // new year's eve in UTC is still december on the server.
+            let now = Utc.with_ymd_and_hms(2025, 1, 1, 2, 0, 0).unwrap();
+            let (_, unlock) = Day::next_unlock(now).unwrap();
+            assert_eq!(unlock, Utc.with_ymd_and_hms(2025, 12, 1, 5, 0, 0).unwrap());
+        }
+    }
 }
 
 /* -------------------------------------------------------------------------- */
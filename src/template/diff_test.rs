@@ -0,0 +1,247 @@
+/// Differential testing of alternative implementations of a part, see `cargo diff-test`.
+///
+/// Alternatives are registered with [`solution!`](crate::solution), e.g. `solution!(6, alternatives = [part_two_naive => 2])`.
+/// Every implementation of a part is run on the examples, the puzzle input and generated inputs and has to
+/// return the same result as the main implementation (`part_one` or `part_two`).
+use std::time::{Duration, Instant};
+use std::{env, fs, process};
+
+use crate::template::generator::{Generator, DEFAULT_SEED};
+use crate::template::runner::Implementation;
+use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+/// Number of generated inputs and their size used when the corresponding flag is missing.
+/// Inputs are kept small, naive implementations can be slow.
+pub const DEFAULT_CASES: u64 = 10;
+pub const DEFAULT_SIZE: usize = 100;
+
+/// Settings passed to a solution binary by `cargo diff-test`.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    /// Number of generated inputs, with seeds counting up from `seed`.
+    pub cases: u64,
+    pub seed: u64,
+    pub size: usize,
+}
+
+impl Options {
+    pub fn to_args(&self) -> Vec<String> {
+        vec![
+            "--diff-test".into(),
+            "--cases".into(),
+            self.cases.to_string(),
+            "--seed".into(),
+            self.seed.to_string(),
+            "--size".into(),
+            self.size.to_string(),
+        ]
+    }
+
+    /// Read settings passed to a solution binary, see [`Options::to_args`]. `None` if no diff test was requested.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        if !args.iter().any(|x| x == "--diff-test") {
+            return None;
+        }
+
+        let value = |flag: &str| {
+            let pos = args.iter().position(|x| x == flag)?;
+            args.get(pos + 1)
+        };
+
+        Some(Self {
+            cases: value("--cases")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(DEFAULT_CASES),
+            seed: value("--seed")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(DEFAULT_SEED),
+            size: value("--size")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(DEFAULT_SIZE),
+        })
+    }
+}
+
+/// An input to run all implementations on. Examples can differ per part.
+struct Source {
+    label: String,
+    inputs: [Option<String>; 2],
+}
+
+/// Run every implementation on every input, print results and timings side by side and exit with an error on mismatches.
+pub fn run(
+    day: Day,
+    implementations: &[Implementation],
+    generator: Option<Generator>,
+    options: &Options,
+) {
+    if !(1..=2).any(|part| implementations.iter().filter(|i| i.part == part).count() > 1) {
+        eprintln!(
+            "Day {day} has no alternative implementations. Register them with `solution!({}, alternatives = [part_two_naive => 2])`.",
+            day.into_inner()
+        );
+        process::exit(1);
+    }
+
+    let name_width = implementations
+        .iter()
+        .map(|i| i.name.len())
+        .max()
+        .unwrap_or_default();
+
+    let mut checked = 0;
+    let mut mismatches = 0;
+
+    for source in collect_sources(day, generator, options) {
+        println!("{ANSI_BOLD}{}{ANSI_RESET}", source.label);
+
+        for part in 1..=2 {
+            let Some(input) = &source.inputs[usize::from(part - 1)] else {
+                continue;
+            };
+
+            let candidates: Vec<&Implementation> =
+                implementations.iter().filter(|i| i.part == part).collect();
+
+            let results: Vec<(Option<String>, Duration)> = candidates
+                .iter()
+                .map(|implementation| {
+                    let timer = Instant::now();
+                    let result = (implementation.func)(input);
+                    (result, timer.elapsed())
+                })
+                .collect();
+
+            let agreement = agreement(&results.iter().map(|(r, _)| r).collect::<Vec<_>>());
+
+            for (i, (implementation, (result, duration))) in
+                candidates.iter().zip(&results).enumerate()
+            {
+                let label = if i == 0 {
+                    format!("Part {part}")
+                } else {
+                    String::new()
+                };
+                let mark = match (i, agreement[i]) {
+                    (0, _) => "",
+                    (_, true) => " ✓",
+                    (_, false) => " ✖ differs",
+                };
+
+                println!(
+                    "  {label:<6}  {:<name_width$}  {:<20} {ANSI_ITALIC}({duration:.1?}){ANSI_RESET}{mark}",
+                    implementation.name,
+                    format_result(result.as_ref()),
+                );
+            }
+
+            if candidates.len() > 1 {
+                checked += 1;
+                if agreement.contains(&false) {
+                    mismatches += 1;
+                }
+            }
+        }
+    }
+
+    println!();
+    if mismatches == 0 {
+        println!("Checked {checked} results, all implementations agree.");
+    } else {
+        println!("{mismatches} of {checked} results differ between implementations.");
+        process::exit(1);
+    }
+}
+
+fn collect_sources(day: Day, generator: Option<Generator>, options: &Options) -> Vec<Source> {
+    let cwd = env::current_dir().unwrap();
+    let read = |path: String| {
+        fs::read_to_string(cwd.join(path))
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+    };
+
+    let mut sources = vec![];
+
+    let example = read(format!("data/examples/{day}.txt"));
+    let example_parts = [1, 2]
+        .map(|part| read(format!("data/examples/{day}-{part}.txt")).or_else(|| example.clone()));
+    if example_parts.iter().any(Option::is_some) {
+        sources.push(Source {
+            label: "example".into(),
+            inputs: example_parts,
+        });
+    }
+
+    match read(format!("data/inputs/{day}.txt")) {
+        Some(input) => sources.push(Source {
+            label: "input".into(),
+            inputs: [Some(input.clone()), Some(input)],
+        }),
+        None => println!("{ANSI_ITALIC}No puzzle input found, skipping it.{ANSI_RESET}"),
+    }
+
+    match generator {
+        Some(generate) => {
+            for seed in options.seed..options.seed.saturating_add(options.cases) {
+                let input = generate(seed, options.size);
+                sources.push(Source {
+                    label: format!("generated (seed {seed}, size {})", options.size),
+                    inputs: [Some(input.clone()), Some(input)],
+                });
+            }
+        }
+        None => println!(
+            "{ANSI_ITALIC}No input generator registered, skipping generated inputs.{ANSI_RESET}"
+        ),
+    }
+
+    sources
+}
+
+/// Whether each result matches the first (reference) result.
+fn agreement(results: &[&Option<String>]) -> Vec<bool> {
+    results.iter().map(|result| *result == results[0]).collect()
+}
+
+/// Multi-line results are shortened to their first line.
+fn format_result(result: Option<&String>) -> String {
+    match result {
+        None => "✖".into(),
+        Some(result) => match result.split_once('\n') {
+            Some((first, _)) => format!("{first} …"),
+            None => result.clone(),
+        },
+    }
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{agreement, format_result, Options};
+
+    #[test]
+    fn roundtrips_args() {
+        let options = Options {
+            cases: 3,
+            seed: 7,
+            size: 50,
+        };
+        assert_eq!(Options::from_args(&options.to_args()), Some(options));
+        assert_eq!(Options::from_args(&["--time".into()]), None);
+    }
+
+    #[test]
+    fn compares_to_reference() {
+        let (a, b, none) = (Some("6".to_string()), Some("7".to_string()), None);
+        assert_eq!(agreement(&[&a, &a, &b]), vec![true, true, false]);
+        assert_eq!(agreement(&[&none, &a]), vec![true, false]);
+    }
+
+    #[test]
+    fn shortens_results() {
+        assert_eq!(format_result(Some(&"42".to_string())), "42");
+        assert_eq!(format_result(Some(&"#.\n.#".to_string())), "#. …");
+        assert_eq!(format_result(None), "✖");
+    }
+}
@@ -0,0 +1,419 @@
+/// A mutation-based fuzzer for solutions, see `cargo fuzz-test`.
+///
+/// Examples and the puzzle input are mutated at random (flipped characters, deleted and duplicated ranges,
+/// shuffled lines, huge numbers, ...) and fed to every implementation of a part. Panics are minimized to a small
+/// reproducer, which is saved to `data/fuzz/`. Runs that exceed the timeout are saved as they are: a hung run can
+/// not be stopped, so the implementation is skipped for the remaining iterations instead. The same applies to a
+/// candidate that hangs while minimizing, so at most one thread per implementation is left behind.
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::io::{stdout, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{env, fs, process, thread};
+
+use crate::template::generator::{Rng, DEFAULT_SEED};
+use crate::template::runner::Implementation;
+use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+/// Number of mutated inputs and the time limit per run used when the corresponding flag is missing.
+pub const DEFAULT_ITERATIONS: u64 = 1000;
+pub const DEFAULT_TIMEOUT_MS: u64 = 1000;
+
+/// Upper bound of runs spent on minimizing a single reproducer.
+const MINIMIZE_BUDGET: usize = 2000;
+
+/// Characters that tend to break parsers.
+const INTERESTING_CHARS: [char; 16] = [
+    '0', '9', '\n', ' ', ',', ':', '|', '-', '#', '.', '^', 'x', '\t', '\0', 'é', '🎄',
+];
+
+/// Numbers that tend to break arithmetic.
+const INTERESTING_NUMBERS: [&str; 5] = ["0", "-1", "4294967296", "99999999999999999999", "00"];
+
+/// Settings passed to a solution binary by `cargo fuzz-test`.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub iterations: u64,
+    pub seed: u64,
+    pub timeout_ms: u64,
+}
+
+impl Options {
+    pub fn to_args(&self) -> Vec<String> {
+        vec![
+            "--fuzz".into(),
+            "--iterations".into(),
+            self.iterations.to_string(),
+            "--seed".into(),
+            self.seed.to_string(),
+            "--timeout".into(),
+            self.timeout_ms.to_string(),
+        ]
+    }
+
+    /// Read settings passed to a solution binary, see [`Options::to_args`]. `None` if no fuzzing was requested.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        if !args.iter().any(|x| x == "--fuzz") {
+            return None;
+        }
+
+        let value = |flag: &str| {
+            let pos = args.iter().position(|x| x == flag)?;
+            args.get(pos + 1)?.parse::<u64>().ok()
+        };
+
+        Some(Self {
+            iterations: value("--iterations").unwrap_or(DEFAULT_ITERATIONS),
+            seed: value("--seed").unwrap_or(DEFAULT_SEED),
+            timeout_ms: value("--timeout").unwrap_or(DEFAULT_TIMEOUT_MS),
+        })
+    }
+}
+
+/// The result of running an implementation on a single input.
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    Ok,
+    Panic { message: String, location: String },
+    Timeout,
+}
+
+thread_local! {
+    /// Location of the last panic on this thread, set by the panic hook.
+    static PANIC_LOCATION: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Feed mutated inputs to every implementation and exit with an error if any of them panicked or timed out.
+pub fn run(day: Day, implementations: &[Implementation], options: &Options) {
+    let corpus = collect_corpus(day);
+    if corpus.is_empty() {
+        eprintln!("Day {day} has no example or puzzle input to mutate.");
+        process::exit(1);
+    }
+
+    // NOTE: panics are expected, record their location instead of printing them.
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(|info| {
+        let location = info.location().map(ToString::to_string).unwrap_or_default();
+        PANIC_LOCATION.with(|l| *l.borrow_mut() = location);
+    }));
+
+    let findings = fuzz(day, implementations, &corpus, options);
+
+    panic::set_hook(previous_hook);
+
+    println!();
+    println!();
+
+    // NOTE: exit explicitly, so hung runs do not keep the process alive.
+    if findings == 0 {
+        println!("No panics or timeouts found.");
+        process::exit(0);
+    } else {
+        println!("Found {findings} problem(s), reproducers were saved to \"data/fuzz\".");
+        process::exit(1);
+    }
+}
+
+/// Run all iterations and return the number of problems found.
+fn fuzz(
+    day: Day,
+    implementations: &[Implementation],
+    corpus: &[String],
+    options: &Options,
+) -> usize {
+    let timeout = Duration::from_millis(options.timeout_ms);
+    let mut rng = Rng::new(options.seed);
+    let mut seen: HashSet<(&str, String)> = HashSet::new();
+    let mut hung: HashSet<&str> = HashSet::new();
+    let mut findings = 0;
+
+    println!(
+        "{ANSI_BOLD}Day {day}{ANSI_RESET} {ANSI_ITALIC}({} inputs, seed {}){ANSI_RESET}",
+        corpus.len(),
+        options.seed
+    );
+
+    for iteration in 1..=options.iterations {
+        let base: &String = rng.choose(corpus);
+        let input = mutate(base, &mut rng);
+
+        for implementation in implementations {
+            if hung.contains(implementation.name) {
+                continue;
+            }
+
+            match run_case(implementation.func, &input, timeout) {
+                Outcome::Ok => {}
+                Outcome::Panic { message, location } => {
+                    if !seen.insert((implementation.name, location.clone())) {
+                        continue;
+                    }
+
+                    // NOTE: a hung candidate leaves its thread running, so stop minimizing after the first one.
+                    let timed_out = Cell::new(false);
+                    let reproducer = minimize(&input, |candidate| {
+                        if timed_out.get() {
+                            return false;
+                        }
+                        match run_case(implementation.func, candidate, timeout) {
+                            Outcome::Panic { location: l, .. } => l == location,
+                            Outcome::Timeout => {
+                                timed_out.set(true);
+                                false
+                            }
+                            Outcome::Ok => false,
+                        }
+                    });
+
+                    print!("\r");
+                    println!(
+                        "✖ {} panicked at {location}: {message}",
+                        implementation.name
+                    );
+                    save_reproducer(day, implementation.name, &location, &reproducer);
+                    findings += 1;
+
+                    if timed_out.get() {
+                        hung.insert(implementation.name);
+                        println!(
+                            "✖ {} did not finish within {}ms while minimizing, skipping it from now on.",
+                            implementation.name, options.timeout_ms
+                        );
+                    }
+                }
+                Outcome::Timeout => {
+                    hung.insert(implementation.name);
+
+                    print!("\r");
+                    println!(
+                        "✖ {} did not finish within {}ms, skipping it from now on.",
+                        implementation.name, options.timeout_ms
+                    );
+                    save_reproducer(day, implementation.name, "timeout", &input);
+                    findings += 1;
+                }
+            }
+        }
+
+        if iteration % 50 == 0 || iteration == options.iterations {
+            print!(
+                "\r{ANSI_ITALIC}{iteration} / {} iterations{ANSI_RESET}",
+                options.iterations
+            );
+            let _ = stdout().flush();
+        }
+    }
+
+    findings
+}
+
+fn collect_corpus(day: Day) -> Vec<String> {
+    let cwd = env::current_dir().unwrap();
+
+    [
+        format!("data/examples/{day}.txt"),
+        format!("data/examples/{day}-1.txt"),
+        format!("data/examples/{day}-2.txt"),
+        format!("data/inputs/{day}.txt"),
+    ]
+    .into_iter()
+    .filter_map(|path| fs::read_to_string(cwd.join(path)).ok())
+    .filter(|s| !s.trim().is_empty())
+    .collect()
+}
+
+/// Run `func` on a separate thread, so panics can be caught and hung runs abandoned.
+fn run_case(func: fn(&str) -> Option<String>, input: &str, timeout: Duration) -> Outcome {
+    let (tx, rx) = mpsc::channel();
+    let input = input.to_string();
+
+    thread::spawn(move || {
+        let outcome = match panic::catch_unwind(AssertUnwindSafe(|| func(&input))) {
+            Ok(_) => Outcome::Ok,
+            Err(payload) => Outcome::Panic {
+                message: panic_message(payload.as_ref()),
+                location: PANIC_LOCATION.with(|l| l.borrow().clone()),
+            },
+        };
+        let _ = tx.send(outcome);
+    });
+
+    rx.recv_timeout(timeout).unwrap_or(Outcome::Timeout)
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<unknown panic payload>".into())
+}
+
+/// Apply one to three random mutations to `input`.
+fn mutate(input: &str, rng: &mut Rng) -> String {
+    let mut chars: Vec<char> = input.chars().collect();
+
+    for _ in 0..rng.range(1..=3) {
+        let len = chars.len() as u64;
+        let pos = rng.range(0..=len) as usize;
+        let end = (pos + rng.range(1..=8) as usize).min(chars.len());
+
+        match rng.range(0..=6) {
+            0 if pos < chars.len() => chars[pos] = *rng.choose(&INTERESTING_CHARS),
+            1 => chars.insert(pos, *rng.choose(&INTERESTING_CHARS)),
+            2 => {
+                chars.drain(pos..end);
+            }
+            3 => {
+                let range: Vec<char> = chars[pos..end].to_vec();
+                chars.splice(pos..pos, range);
+            }
+            4 => chars.truncate(pos),
+            5 => {
+                let number = rng.choose(&INTERESTING_NUMBERS);
+                chars.splice(pos..pos, number.chars());
+            }
+            _ => {
+                let text: String = chars.iter().collect();
+                let mut lines: Vec<&str> = text.lines().collect();
+                if !lines.is_empty() {
+                    let a = rng.range(0..=lines.len() as u64 - 1) as usize;
+                    let b = rng.range(0..=lines.len() as u64 - 1) as usize;
+                    match rng.range(0..=2) {
+                        0 => lines.swap(a, b),
+                        1 => {
+                            lines.remove(a);
+                        }
+                        _ => lines.insert(a, lines[b]),
+                    }
+                }
+                chars = lines.join("\n").chars().collect();
+                chars.push('\n');
+            }
+        }
+    }
+
+    chars.into_iter().collect()
+}
+
+/// Shrink `input` while `fails` still holds, first by removing lines, then by removing characters.
+fn minimize(input: &str, fails: impl Fn(&str) -> bool) -> String {
+    let mut budget = MINIMIZE_BUDGET;
+
+    let lines: Vec<String> = input.lines().map(|l| format!("{l}\n")).collect();
+    let lines = shrink(lines, &mut budget, |lines| fails(&lines.concat()));
+
+    let chars: Vec<char> = lines.concat().chars().collect();
+    let chars = shrink(chars, &mut budget, |chars| {
+        fails(&chars.iter().collect::<String>())
+    });
+
+    chars.into_iter().collect()
+}
+
+/// Remove chunks of halving size as long as `fails` still holds for the remainder.
+fn shrink<T: Clone>(mut items: Vec<T>, budget: &mut usize, fails: impl Fn(&[T]) -> bool) -> Vec<T> {
+    let mut chunk = items.len().div_ceil(2).max(1);
+
+    while chunk > 0 && *budget > 0 {
+        let mut start = 0;
+
+        while start < items.len() && *budget > 0 {
+            let end = (start + chunk).min(items.len());
+            let candidate: Vec<T> = [&items[..start], &items[end..]].concat();
+            *budget -= 1;
+
+            if fails(&candidate) {
+                items = candidate;
+            } else {
+                start += chunk;
+            }
+        }
+
+        chunk /= 2;
+    }
+
+    items
+}
+
+fn save_reproducer(day: Day, name: &str, signature: &str, input: &str) {
+    let mut hasher = DefaultHasher::new();
+    signature.hash(&mut hasher);
+    #[allow(clippy::cast_possible_truncation)]
+    let hash = hasher.finish() as u32;
+
+    let dir = PathBuf::from("data").join("fuzz");
+    let path = dir.join(format!("{day}-{name}-{hash:08x}.txt"));
+
+    match fs::create_dir_all(&dir).and_then(|()| fs::write(&path, input)) {
+        Ok(()) => println!(
+            "  {ANSI_ITALIC}reproducer ({} bytes): {}{ANSI_RESET}",
+            input.len(),
+            path.display()
+        ),
+        Err(e) => eprintln!("  could not write reproducer to {}: {e}", path.display()),
+    }
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{minimize, mutate, run_case, Options, Outcome};
+    use crate::template::generator::Rng;
+
+    #[test]
+    fn roundtrips_args() {
+        let options = Options {
+            iterations: 10,
+            seed: 3,
+            timeout_ms: 50,
+        };
+        assert_eq!(Options::from_args(&options.to_args()), Some(options));
+        assert_eq!(Options::from_args(&["--time".into()]), None);
+    }
+
+    #[test]
+    fn mutates_deterministically() {
+        let input = "1 2 3\n4 5 6\n";
+        let a = mutate(input, &mut Rng::new(1));
+        let b = mutate(input, &mut Rng::new(1));
+        assert_eq!(a, b);
+
+        let mut rng = Rng::new(2);
+        assert!((0..20).any(|_| mutate(input, &mut rng) != input));
+    }
+
+    #[test]
+    fn minimizes_reproducers() {
+        let input = "abc\ndef\nghi x jkl\nmno\n";
+        assert_eq!(minimize(input, |s| s.contains('x')), "x");
+        assert_eq!(minimize(input, |s| s.lines().count() >= 2), "\n\n");
+    }
+
+    #[test]
+    fn catches_panics_and_timeouts() {
+        let timeout = Duration::from_millis(100);
+        assert_eq!(run_case(|_| None, "", timeout), Outcome::Ok);
+
+        let outcome = run_case(|s| Some(s[10..].to_string()), "", timeout);
+        assert!(matches!(outcome, Outcome::Panic { .. }));
+
+        let outcome = run_case(
+            |_| loop {
+                std::thread::sleep(Duration::from_millis(10));
+            },
+            "",
+            timeout,
+        );
+        assert_eq!(outcome, Outcome::Timeout);
+    }
+}
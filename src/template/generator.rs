@@ -0,0 +1,124 @@
+/// Input generators for stress-testing solutions, see `cargo stress`.
+///
+/// A solution opts in by defining `pub fn generate(seed: u64, size: usize) -> String` and passing `generate` to
+/// [`solution!`](crate::solution), e.g. `solution!(7, generate)`. Generators must be deterministic for a given seed
+/// and should scale the input roughly linearly with `size` (lines, cells, files, ...).
+use std::ops::RangeInclusive;
+
+/// Signature of a solution's input generator.
+pub type Generator = fn(u64, usize) -> String;
+
+/// Seed and size used when the corresponding flag is missing.
+pub const DEFAULT_SEED: u64 = 0;
+pub const DEFAULT_SIZE: usize = 1000;
+
+/// Arguments that make a solution binary generate its input instead of reading `data/inputs`.
+pub fn to_args(seed: u64, size: usize) -> Vec<String> {
+    vec![
+        "--generate".into(),
+        "--seed".into(),
+        seed.to_string(),
+        "--size".into(),
+        size.to_string(),
+    ]
+}
+
+/// Read seed and size passed to a solution binary, see [`to_args`]. `None` if no input should be generated.
+pub fn from_args(args: &[String]) -> Option<(u64, usize)> {
+    if !args.iter().any(|x| x == "--generate") {
+        return None;
+    }
+
+    let value = |flag: &str| {
+        let pos = args.iter().position(|x| x == flag)?;
+        args.get(pos + 1)
+    };
+
+    let seed = value("--seed")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_SEED);
+    let size = value("--size")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_SIZE);
+
+    Some((seed, size))
+}
+
+/// A small, seedable pseudo-random number generator (SplitMix64).
+/// Not suitable for anything but generating puzzle inputs, but stable across platforms and releases.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in the inclusive range.
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (start, end) = (*range.start(), *range.end());
+        assert!(start <= end, "empty range {start}..={end}");
+
+        match (end - start).checked_add(1) {
+            Some(span) => start + self.next_u64() % span,
+            None => self.next_u64(),
+        }
+    }
+
+    /// `true` with the given probability.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64) < probability
+    }
+
+    /// A random element of a non-empty slice.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        let max = u64::try_from(items.len() - 1).unwrap_or(u64::MAX);
+        &items[usize::try_from(self.range(0..=max)).unwrap_or_default()]
+    }
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{from_args, to_args, Rng};
+
+    #[test]
+    fn is_deterministic() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        for _ in 0..5 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn stays_in_range() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            assert!((3..=9).contains(&rng.range(3..=9)));
+        }
+        assert_eq!(rng.range(5..=5), 5);
+        rng.range(0..=u64::MAX);
+
+        let items = ['a', 'b', 'c'];
+        assert!(items.contains(rng.choose(&items)));
+    }
+
+    #[test]
+    fn roundtrips_args() {
+        assert_eq!(from_args(&to_args(3, 500)), Some((3, 500)));
+        assert_eq!(from_args(&["--generate".into()]), Some((0, 1000)));
+        assert_eq!(from_args(&["--time".into()]), None);
+    }
+}
@@ -0,0 +1,238 @@
+/// Counts retired instructions of a solution part. Unlike wall time, this metric is comparable between machines.
+/// See `cargo time --instructions`.
+///
+/// With the `instructions` feature, instructions are read from a hardware counter via `perf_event_open` on Linux.
+/// If that is not available (other platforms, containers, `perf_event_paranoid`), the solution is re-run under
+/// `valgrind --tool=cachegrind` instead.
+use std::env;
+
+/// Set in the environment of cachegrind child processes to the part that should run. `0` runs no part.
+const CACHEGRIND_PART_ENV: &str = "AOC_CACHEGRIND_PART";
+
+/// Count instructions of a single execution of `func`.
+pub fn count<I: Clone, T>(func: impl Fn(I) -> T, input: I, part: u8) -> Option<u64> {
+    if let Some(instructions) = perf::count(func, input) {
+        return Some(instructions);
+    }
+
+    match cachegrind::count(part) {
+        Ok(instructions) => Some(instructions),
+        Err(e) => {
+            eprintln!("Could not count instructions: {e}");
+            None
+        }
+    }
+}
+
+/// The part to run if this process was started by the cachegrind backend.
+pub fn cachegrind_part() -> Option<u8> {
+    env::var(CACHEGRIND_PART_ENV).ok()?.parse().ok()
+}
+
+/// Format an instruction count with thousands separators.
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(ch);
+    }
+
+    result
+}
+
+pub fn parse_count(s: &str) -> Option<u64> {
+    s.trim().replace(',', "").parse().ok()
+}
+
+#[cfg(not(all(feature = "instructions", target_os = "linux")))]
+mod perf {
+    /// Hardware counters are only available with the `instructions` feature on Linux.
+    pub fn count<I, T>(_func: impl Fn(I) -> T, _input: I) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(all(feature = "instructions", target_os = "linux"))]
+mod perf {
+    use std::fs::File;
+    use std::hint::black_box;
+    use std::io::{self, Read};
+    use std::os::fd::{AsRawFd, FromRawFd};
+
+    use perf_event_open_sys::{bindings, ioctls, perf_event_open};
+
+    /// The lowest count of this many runs is reported.
+    const RUNS: usize = 5;
+
+    /// Count instructions with a hardware counter, `None` if the counter can not be opened.
+    pub fn count<I: Clone, T>(func: impl Fn(I) -> T, input: I) -> Option<u64> {
+        // NOTE: a fresh counter per run, as a reset does not clear the counts of threads that already exited.
+        (0..RUNS)
+            .map(|_| {
+                let counter = Counter::new()?;
+                let cloned = input.clone();
+                counter.measure(|| black_box(func(black_box(cloned))))
+            })
+            .filter_map(Result::ok)
+            .min()
+    }
+
+    /// A hardware counter of instructions retired in user space by the current thread and the threads it spawns.
+    /// Instructions of spawned threads are counted once they exit, e.g. at the end of `thread::scope`.
+    pub struct Counter {
+        file: File,
+    }
+
+    impl Counter {
+        pub fn new() -> io::Result<Self> {
+            let mut attrs = bindings::perf_event_attr {
+                type_: bindings::perf_type_id_PERF_TYPE_HARDWARE,
+                size: u32::try_from(std::mem::size_of::<bindings::perf_event_attr>())
+                    .unwrap_or(bindings::PERF_ATTR_SIZE_VER0),
+                config: u64::from(bindings::perf_hw_id_PERF_COUNT_HW_INSTRUCTIONS),
+                ..Default::default()
+            };
+            attrs.set_disabled(1);
+            attrs.set_exclude_kernel(1);
+            attrs.set_exclude_hv(1);
+            attrs.set_inherit(1);
+
+            // SAFETY: `attrs` is a valid, initialized struct that outlives the call.
+            let fd = unsafe { perf_event_open(&mut attrs, 0, -1, -1, 0) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            // SAFETY: the descriptor was just opened and is owned by nothing else.
+            let file = unsafe { File::from_raw_fd(fd) };
+            Ok(Self { file })
+        }
+
+        /// Count the instructions retired while running `f`.
+        pub fn measure<T>(&self, f: impl FnOnce() -> T) -> io::Result<u64> {
+            let fd = self.file.as_raw_fd();
+
+            // SAFETY: `fd` is a valid perf event descriptor for the lifetime of `self`.
+            unsafe {
+                ioctls::RESET(fd, 0);
+                ioctls::ENABLE(fd, 0);
+            }
+            let result = f();
+            // SAFETY: see above.
+            unsafe {
+                ioctls::DISABLE(fd, 0);
+            }
+            drop(result);
+
+            let mut buf = [0_u8; 8];
+            (&self.file).read_exact(&mut buf)?;
+            Ok(u64::from_ne_bytes(buf))
+        }
+    }
+}
+
+mod cachegrind {
+    use std::process::{Command, Stdio};
+
+    use super::{parse_count, CACHEGRIND_PART_ENV};
+
+    /// Run the current binary under cachegrind twice, with and without `part`, and return the difference.
+    /// This subtracts the cost of process startup and reading the input.
+    pub fn count(part: u8) -> Result<u64, String> {
+        let total = run(part)?;
+        let baseline = run(0)?;
+        Ok(total.saturating_sub(baseline))
+    }
+
+    fn run(part: u8) -> Result<u64, String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+
+        let output = Command::new("valgrind")
+            .args([
+                "--tool=cachegrind",
+                "--cache-sim=no",
+                "--cachegrind-out-file=/dev/null",
+            ])
+            .arg(exe)
+            // NOTE: forward arguments, so e.g. `--variant` selects the same implementation.
+            .args(std::env::args().skip(1))
+            .env(CACHEGRIND_PART_ENV, part.to_string())
+            .stdout(Stdio::null())
+            .output()
+            .map_err(|e| {
+                format!(
+                    "hardware counters are not available and valgrind could not be started: {e}"
+                )
+            })?;
+
+        parse_refs(&String::from_utf8_lossy(&output.stderr))
+            .ok_or_else(|| "could not parse cachegrind output.".into())
+    }
+
+    /// Parse the `I refs` summary line, e.g. `==123== I   refs:      1,234,567`.
+    pub fn parse_refs(stderr: &str) -> Option<u64> {
+        stderr.lines().find_map(|line| {
+            let (label, count) = line.split_once("refs:")?;
+            label
+                .trim_end()
+                .ends_with(" I")
+                .then(|| parse_count(count))?
+        })
+    }
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{cachegrind::parse_refs, format_count, parse_count};
+
+    #[test]
+    fn formats_counts() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(123), "123");
+        assert_eq!(format_count(1234), "1,234");
+        assert_eq!(format_count(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn parses_counts() {
+        assert_eq!(parse_count("1,234,567"), Some(1_234_567));
+        assert_eq!(parse_count(" 12 "), Some(12));
+        assert_eq!(parse_count("foo"), None);
+    }
+
+    #[test]
+    fn parses_cachegrind_output() {
+        let stderr = [
+            "==4242== Cachegrind, a high-precision tracing profiler",
+            "==4242== ",
+            "==4242== I   refs:      1,234,567",
+        ]
+        .join("\n");
+        assert_eq!(parse_refs(&stderr), Some(1_234_567));
+        assert_eq!(parse_refs("==1== I refs: 42"), Some(42));
+        assert_eq!(parse_refs("==1== D refs: 42"), None);
+    }
+
+    #[cfg(all(feature = "instructions", target_os = "linux"))]
+    #[test]
+    fn counts_spawned_threads() {
+        use std::hint::black_box;
+        use std::thread;
+
+        const ITERATIONS: u64 = 1_000_000;
+
+        let work = |n: u64| (0..n).fold(0_u64, |acc, x| black_box(acc ^ x));
+        let on_thread = |n: u64| thread::scope(|s| s.spawn(|| work(n)).join().unwrap());
+
+        // NOTE: hardware counters are not available everywhere, e.g. in containers.
+        let Some(count) = super::perf::count(on_thread, ITERATIONS) else {
+            return;
+        };
+        assert!(count >= ITERATIONS, "only {count} instructions counted");
+    }
+}
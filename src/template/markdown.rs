@@ -0,0 +1,625 @@
+/// Module that renders the puzzle descriptions saved by aoc-cli (`data/puzzles/NN.md`) to the terminal.
+/// Supports the subset of markdown (and inline HTML) that appears in these files.
+use crate::template::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+const ANSI_CODE: &str = "\x1b[36m";
+const ANSI_UNDERLINE: &str = "\x1b[4m";
+
+const AOC_URL: &str = "https://adventofcode.com";
+
+/// Which part of the puzzle to render.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    All,
+    Part(u8),
+}
+
+/// Renders a puzzle description, word-wrapped to `width` columns.
+/// Returns `None` if the requested part is not contained in the description.
+pub fn render(markdown: &str, width: usize, section: Section) -> Option<String> {
+    let markdown = select_section(markdown, section)?;
+    let blocks = parse_blocks(markdown);
+
+    let mut links = vec![];
+    let mut out: Vec<String> = vec![];
+
+    for block in blocks {
+        if !out.is_empty() {
+            out.push(String::new());
+        }
+
+        match block {
+            Block::Heading(text) => {
+                let style = Style {
+                    bold: true,
+                    ..Style::default()
+                };
+                let spans = parse_inline(&text, style, &mut links);
+                out.extend(wrap(&spans, width, "", ""));
+            }
+            Block::Paragraph(text) => {
+                let spans = parse_inline(&text, Style::default(), &mut links);
+                out.extend(wrap(&spans, width, "", ""));
+            }
+            Block::ListItem(text) => {
+                let spans = parse_inline(&text, Style::default(), &mut links);
+                out.extend(wrap(&spans, width, "  • ", "    "));
+            }
+            Block::Code(lines) => out.extend(render_code(&lines)),
+            Block::Rule => out.push("─".repeat(width.min(40))),
+        }
+    }
+
+    // NOTE: list items are rendered without a blank line in between.
+    let mut rendered = out.join("\n").replace("\n\n  • ", "\n  • ");
+
+    if !links.is_empty() {
+        rendered.push_str("\n\n");
+        for (i, url) in links.iter().enumerate() {
+            rendered.push_str(&format!("[{}]: {url}\n", i + 1));
+        }
+    }
+
+    Some(rendered.trim_end().to_string())
+}
+
+/// Extracts the puzzle title from a description, e.g. `Day 1: Historian Hysteria`.
+pub fn title(markdown: &str) -> Option<String> {
+    let line = markdown.lines().find(|l| l.contains("--- Day "))?;
+    let title = line
+        .trim_start_matches(['#', ' ', '\\'])
+        .trim_matches(['-', ' ']);
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Splits the description into part one and part two at the "Part Two" heading.
+fn select_section(markdown: &str, section: Section) -> Option<&str> {
+    let part_two = markdown
+        .match_indices("--- Part Two ---")
+        .next()
+        .map(|(pos, _)| markdown[..pos].rfind('\n').map_or(0, |p| p + 1));
+
+    match (section, part_two) {
+        (Section::All, _) | (Section::Part(1), None) => Some(markdown),
+        (Section::Part(1), Some(pos)) => Some(&markdown[..pos]),
+        (Section::Part(2), Some(pos)) => Some(&markdown[pos..]),
+        _ => None,
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[derive(Debug, PartialEq, Eq)]
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    ListItem(String),
+    Code(Vec<String>),
+    Rule,
+}
+
+fn is_underline(line: &str) -> bool {
+    let line = line.trim();
+    line.len() >= 3 && (line.chars().all(|c| c == '-') || line.chars().all(|c| c == '='))
+}
+
+fn list_item(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    for bullet in ["* ", "- ", "+ "] {
+        if let Some(rest) = trimmed.strip_prefix(bullet) {
+            return Some(rest);
+        }
+    }
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        return trimmed[digits..].strip_prefix(". ");
+    }
+    None
+}
+
+fn parse_blocks(markdown: &str) -> Vec<Block> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut blocks = vec![];
+    let mut paragraph: Vec<&str> = vec![];
+    let mut i = 0;
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(paragraph.join(" ")));
+            paragraph.clear();
+        }
+    };
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+            i += 1;
+        } else if trimmed.starts_with("```") {
+            flush(&mut paragraph, &mut blocks);
+            let code: Vec<String> = lines[i + 1..]
+                .iter()
+                .take_while(|l| !l.trim_start().starts_with("```"))
+                .map(|l| (*l).to_string())
+                .collect();
+            i += code.len() + 2;
+            blocks.push(Block::Code(code));
+        } else if (line.starts_with("    ") || line.starts_with('\t')) && paragraph.is_empty() {
+            let mut code = vec![];
+            while i < lines.len() {
+                let l = lines[i];
+                if let Some(rest) = l.strip_prefix("    ") {
+                    code.push(rest.to_string());
+                } else if let Some(rest) = l.strip_prefix('\t') {
+                    code.push(rest.to_string());
+                } else if l.trim().is_empty()
+                    && lines.get(i + 1).is_some_and(|n| n.starts_with("    "))
+                {
+                    code.push(String::new());
+                } else {
+                    break;
+                }
+                i += 1;
+            }
+            blocks.push(Block::Code(code));
+        } else if let Some(heading) = trimmed.strip_prefix('#') {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading(
+                heading.trim_start_matches('#').trim().into(),
+            ));
+            i += 1;
+        } else if paragraph.is_empty() && lines.get(i + 1).is_some_and(|l| is_underline(l)) {
+            blocks.push(Block::Heading(trimmed.into()));
+            i += 2;
+        } else if paragraph.is_empty() && is_underline(trimmed) {
+            blocks.push(Block::Rule);
+            i += 1;
+        } else if let Some(item) = list_item(line) {
+            flush(&mut paragraph, &mut blocks);
+            let mut text = item.to_string();
+            i += 1;
+            // continuation lines of a list item are indented.
+            while i < lines.len()
+                && lines[i].starts_with(' ')
+                && !lines[i].trim().is_empty()
+                && list_item(lines[i]).is_none()
+            {
+                text.push(' ');
+                text.push_str(lines[i].trim());
+                i += 1;
+            }
+            blocks.push(Block::ListItem(text));
+        } else {
+            paragraph.push(trimmed);
+            i += 1;
+        }
+    }
+
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Style {
+    bold: bool,
+    italic: bool,
+    code: bool,
+    link: bool,
+}
+
+impl Style {
+    fn prefix(self) -> String {
+        let mut s = String::new();
+        if self.bold {
+            s.push_str(ANSI_BOLD);
+        }
+        if self.italic {
+            s.push_str(ANSI_ITALIC);
+        }
+        if self.code {
+            s.push_str(ANSI_CODE);
+        }
+        if self.link {
+            s.push_str(ANSI_UNDERLINE);
+        }
+        s
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Span {
+    text: String,
+    style: Style,
+}
+
+fn push_char(spans: &mut Vec<Span>, ch: char, style: Style) {
+    match spans.last_mut() {
+        Some(span) if span.style == style => span.text.push(ch),
+        _ => spans.push(Span {
+            text: ch.to_string(),
+            style,
+        }),
+    }
+}
+
+/// Matches an HTML tag at the start of `s`, returning the tag name (prefixed with `/` for closing tags)
+/// and the length of the tag.
+fn match_tag(s: &str) -> Option<(String, usize)> {
+    let rest = s.strip_prefix('<')?;
+    let end = rest.find('>')?;
+    let inner = &rest[..end];
+
+    if inner.contains('<') || inner.contains('\n') {
+        return None;
+    }
+
+    let (closing, name) = match inner.strip_prefix('/') {
+        Some(name) => (true, name),
+        None => (false, inner),
+    };
+    let name: String = name.chars().take_while(char::is_ascii_alphabetic).collect();
+
+    if name.is_empty() {
+        return None;
+    }
+
+    let tag = if closing { format!("/{name}") } else { name };
+    Some((tag, end + 2))
+}
+
+/// Matches a markdown link `[text](url)` at the start of `s`, returning text, url and length.
+fn match_link(s: &str) -> Option<(&str, &str, usize)> {
+    let rest = s.strip_prefix('[')?;
+    let text_end = rest.find("](")?;
+    let text = &rest[..text_end];
+    let url_start = text_end + 2;
+    let url_len = rest[url_start..].find(')')?;
+    let url = &rest[url_start..url_start + url_len];
+    Some((text, url, 1 + url_start + url_len + 1))
+}
+
+/// Matches a code span that is emphasized as a whole at the start of `s`, returning its content and length.
+fn code_emphasis(s: &str) -> Option<(&str, usize)> {
+    let rest = s.strip_prefix('`')?;
+    let end = rest.find('`')?;
+    let inner = rest[..end]
+        .strip_prefix('*')?
+        .strip_suffix('*')?
+        .trim_matches('*');
+    (!inner.is_empty()).then_some((inner, end + 2))
+}
+
+fn parse_inline(text: &str, base: Style, links: &mut Vec<String>) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut style = base;
+    let mut em_depth = 0_u32;
+    let mut strong = false;
+
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let (pos, ch) = chars[idx];
+        let rest = &text[pos..];
+        let prev = idx.checked_sub(1).map(|i| chars[i].1);
+        let next = chars.get(idx + 1).map(|c| c.1);
+
+        let mut skip = |len: usize| {
+            let target = pos + len;
+            while idx < chars.len() && chars[idx].0 < target {
+                idx += 1;
+            }
+        };
+
+        match ch {
+            '\\' if next.is_some_and(|c| c.is_ascii_punctuation()) => {
+                push_char(&mut spans, next.unwrap_or_default(), style);
+                skip(2);
+            }
+            '`' if !style.code && code_emphasis(rest).is_some() => {
+                // emphasized code, e.g. the answers in examples: `*11*`.
+                let (inner, len) = code_emphasis(rest).unwrap_or_default();
+                let emphasized = Style {
+                    bold: true,
+                    code: true,
+                    ..style
+                };
+                for ch in inner.chars() {
+                    push_char(&mut spans, ch, emphasized);
+                }
+                skip(len);
+            }
+            '`' => {
+                style.code = !style.code;
+                skip(1);
+            }
+            '<' if match_tag(rest).is_some() => {
+                let (tag, len) = match_tag(rest).unwrap_or_default();
+                match tag.as_str() {
+                    "em" | "b" | "strong" => em_depth += 1,
+                    "/em" | "/b" | "/strong" => em_depth = em_depth.saturating_sub(1),
+                    "code" => style.code = true,
+                    "/code" => style.code = false,
+                    _ => {}
+                }
+                style.bold = base.bold || strong || em_depth > 0;
+                skip(len);
+            }
+            '[' if !style.code && match_link(rest).is_some() => {
+                let (link_text, url, len) = match_link(rest).unwrap_or_default();
+                let link_style = Style {
+                    link: true,
+                    ..style
+                };
+                spans.extend(parse_inline(link_text, link_style, links));
+
+                let url = if url.starts_with('/') {
+                    format!("{AOC_URL}{url}")
+                } else {
+                    url.to_string()
+                };
+                links.push(url);
+                spans.push(Span {
+                    text: format!("[{}]", links.len()),
+                    style,
+                });
+                skip(len);
+            }
+            '*' if !style.code && next == Some('*') => {
+                strong = !strong;
+                style.bold = base.bold || strong || em_depth > 0;
+                skip(2);
+            }
+            '*' if !style.code => {
+                style.italic = !style.italic;
+                skip(1);
+            }
+            '_' if !style.code
+                && (style.italic && !next.is_some_and(char::is_alphanumeric)
+                    || !style.italic && !prev.is_some_and(char::is_alphanumeric)) =>
+            {
+                style.italic = !style.italic;
+                skip(1);
+            }
+            _ => {
+                push_char(&mut spans, ch, style);
+                skip(1);
+            }
+        }
+    }
+
+    spans
+}
+
+/* -------------------------------------------------------------------------- */
+
+fn render_span(text: &str, style: Style) -> String {
+    let prefix = style.prefix();
+    if prefix.is_empty() {
+        text.to_string()
+    } else {
+        format!("{prefix}{text}{ANSI_RESET}")
+    }
+}
+
+/// Word-wraps styled spans to `width` columns. The first line is prefixed with `first_indent`,
+/// all following lines with `indent`.
+fn wrap(spans: &[Span], width: usize, first_indent: &str, indent: &str) -> Vec<String> {
+    // split spans into words, each word being a list of styled pieces.
+    let mut words: Vec<Vec<Span>> = vec![];
+    let mut current: Vec<Span> = vec![];
+
+    for span in spans {
+        for (i, part) in span.text.split(' ').enumerate() {
+            if i > 0 && !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            if !part.is_empty() {
+                current.push(Span {
+                    text: part.to_string(),
+                    style: span.style,
+                });
+            }
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    let mut lines = vec![];
+    let mut line: Vec<Span> = vec![];
+    let mut line_width = first_indent.chars().count();
+    let mut prefix = first_indent;
+
+    for word in words {
+        let word_width: usize = word.iter().map(|s| s.text.chars().count()).sum();
+
+        if !line.is_empty() && line_width + 1 + word_width > width {
+            lines.push(format!("{prefix}{}", render_line(&line)));
+            line.clear();
+            prefix = indent;
+            line_width = indent.chars().count();
+        }
+
+        if let Some(last) = line.last() {
+            // NOTE: spaces between two words of the same style share it, e.g. to underline a whole link.
+            let style = if last.style == word[0].style {
+                last.style
+            } else {
+                Style::default()
+            };
+            push_char(&mut line, ' ', style);
+            line_width += 1;
+        }
+
+        for span in word {
+            for ch in span.text.chars() {
+                push_char(&mut line, ch, span.style);
+            }
+        }
+
+        line_width += word_width;
+    }
+
+    if !line.is_empty() {
+        lines.push(format!("{prefix}{}", render_line(&line)));
+    }
+
+    lines
+}
+
+fn render_line(spans: &[Span]) -> String {
+    spans
+        .iter()
+        .map(|s| render_span(&s.text, s.style))
+        .collect()
+}
+
+fn render_code(lines: &[String]) -> Vec<String> {
+    // NOTE: code blocks may contain inline `<em>` tags, which are rendered as bold.
+    let rendered: Vec<(String, usize)> = lines
+        .iter()
+        .map(|line| {
+            let base = Style {
+                code: true,
+                ..Style::default()
+            };
+            let spans = parse_code_line(line, base);
+            let width = spans.iter().map(|s| s.text.chars().count()).sum();
+            (render_line(&spans), width)
+        })
+        .collect();
+
+    let inner_width = rendered.iter().map(|(_, w)| *w).max().unwrap_or(0);
+    let mut out = vec![format!("┌{}┐", "─".repeat(inner_width + 2))];
+
+    for (text, width) in rendered {
+        out.push(format!("│ {text}{} │", " ".repeat(inner_width - width)));
+    }
+
+    out.push(format!("└{}┘", "─".repeat(inner_width + 2)));
+    out
+}
+
+/// Code lines are verbatim except for inline HTML tags.
+fn parse_code_line(line: &str, base: Style) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut style = base;
+    let mut rest = line;
+
+    while let Some(ch) = rest.chars().next() {
+        if let Some((tag, len)) = match_tag(rest) {
+            match tag.as_str() {
+                "em" | "b" | "strong" => style.bold = true,
+                "/em" | "/b" | "/strong" => style.bold = false,
+                _ => {}
+            }
+            rest = &rest[len..];
+        } else {
+            push_char(&mut spans, ch, style);
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+
+    spans
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{parse_blocks, render, title, Block, Section};
+    use crate::template::strip_ansi;
+
+    const PUZZLE: &str = "\\--- Day 1: Historian Hysteria ---
+----------
+
+The *Chief Historian* is always present at the big Christmas sleigh launch, but nobody has seen him in months!
+
+For example:
+
+```
+3   4
+4   3
+```
+
+In the example list above, the pairs would be `<em>11</em>` or `*11*` and the [list](/2024/day/1/input) is long.
+
+* item one
+* item two
+
+\\--- Part Two ---
+----------
+
+Your puzzle answer was `1234`.
+";
+
+    #[test]
+    fn parses_blocks() {
+        let blocks = parse_blocks(PUZZLE);
+        assert_eq!(
+            blocks[0],
+            Block::Heading("\\--- Day 1: Historian Hysteria ---".into())
+        );
+        assert_eq!(blocks[3], Block::Code(vec!["3   4".into(), "4   3".into()]));
+        assert_eq!(blocks[5], Block::ListItem("item one".into()));
+    }
+
+    #[test]
+    fn renders_headings_and_emphasis() {
+        let rendered = render(PUZZLE, 80, Section::All).unwrap();
+        assert!(rendered.contains("\x1b[1m--- Day 1: Historian Hysteria ---"));
+        assert!(rendered.contains("\x1b[3mChief Historian"));
+        assert!(rendered.contains("\x1b[1m\x1b[36m11\x1b[0m or \x1b[1m\x1b[36m11\x1b[0m"));
+    }
+
+    #[test]
+    fn renders_code_blocks_in_a_box() {
+        let rendered = strip_ansi(&render(PUZZLE, 80, Section::All).unwrap());
+        assert!(rendered.contains("┌───────┐\n│ 3   4 │\n│ 4   3 │\n└───────┘"));
+    }
+
+    #[test]
+    fn renders_links_as_footnotes() {
+        let rendered = strip_ansi(&render(PUZZLE, 80, Section::All).unwrap());
+        assert!(rendered.contains("the list[1] is long."));
+        assert!(rendered.ends_with("[1]: https://adventofcode.com/2024/day/1/input"));
+    }
+
+    #[test]
+    fn wraps_to_width() {
+        let rendered = strip_ansi(&render(PUZZLE, 30, Section::Part(1)).unwrap());
+        assert!(rendered
+            .lines()
+            .filter(|l| !l.starts_with('['))
+            .all(|l| l.chars().count() <= 30));
+        assert!(rendered.contains("  • item one\n  • item two"));
+    }
+
+    #[test]
+    fn extracts_title() {
+        assert_eq!(title(PUZZLE).as_deref(), Some("Day 1: Historian Hysteria"));
+        assert_eq!(
+            title("## \\--- Day 12: Garden Groups ---").as_deref(),
+            Some("Day 12: Garden Groups")
+        );
+        assert_eq!(title("no title"), None);
+    }
+
+    #[test]
+    fn selects_part_two() {
+        let rendered = strip_ansi(&render(PUZZLE, 80, Section::Part(2)).unwrap());
+        assert!(rendered.starts_with("--- Part Two ---"));
+        assert!(!rendered.contains("Historian"));
+
+        let part_one = strip_ansi(&render(PUZZLE, 80, Section::Part(1)).unwrap());
+        assert!(!part_one.contains("Part Two"));
+
+        assert!(render("\\--- Day 1 ---\n", 80, Section::Part(2)).is_none());
+    }
+}
@@ -1,20 +1,56 @@
 use std::{env, fs};
 
+pub mod alloc;
 pub mod aoc_cli;
+pub mod bench_config;
 pub mod commands;
+pub mod diff_test;
+pub mod fuzz;
+pub mod generator;
+pub mod instructions;
 pub mod runner;
+pub mod trace;
+pub mod visualize;
 
 pub use day::*;
 
+mod answers;
 mod day;
+mod markdown;
+#[cfg(feature = "profile")]
+mod profiler;
 mod readme_benchmarks;
+mod readme_status;
 mod run_multi;
+mod status;
 mod timings;
 
 pub const ANSI_ITALIC: &str = "\x1b[3m";
 pub const ANSI_BOLD: &str = "\x1b[1m";
 pub const ANSI_RESET: &str = "\x1b[0m";
 
+/// Removes ANSI escape sequences (colors and styles) from a string.
+#[must_use]
+pub fn strip_ansi(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            // skip a CSI sequence up to and including its final byte.
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            result.push(ch);
+        }
+    }
+
+    result
+}
+
 /// Helper function that reads a text file to a string.
 #[must_use]
 pub fn read_file(folder: &str, day: Day) -> String {
@@ -39,19 +75,36 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+/// Instead, a comma-separated list of options can be passed:
+///  - `generate` registers an input generator for `cargo stress`, see [`generator`](crate::template::generator).
+///  - `alternatives = [part_two_naive => 2]` registers alternative implementations of a part for `cargo diff-test`.
+///    They can be run in place of the main implementation with `--variant naive`.
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
-        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2]);
+        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2]; [] []);
     };
     ($day:expr, 1) => {
-        $crate::solution!(@impl $day, [part_one, 1]);
+        $crate::solution!(@impl $day, [part_one, 1]; [] []);
     };
     ($day:expr, 2) => {
-        $crate::solution!(@impl $day, [part_two, 2]);
+        $crate::solution!(@impl $day, [part_two, 2]; [] []);
+    };
+    ($day:expr, $($options:tt)+) => {
+        $crate::solution!(@options $day, [] []; $($options)+);
     };
 
-    (@impl $day:expr, $( [$func:expr, $part:expr] )*) => {
+    (@options $day:expr, [$($generate:ident)?] [$($alt:tt)*]; generate $(, $($rest:tt)*)?) => {
+        $crate::solution!(@options $day, [generate] [$($alt)*]; $($($rest)*)?);
+    };
+    (@options $day:expr, [$($generate:ident)?] [$($alt:tt)*]; alternatives = [$($name:ident => $part:literal),* $(,)?] $(, $($rest:tt)*)?) => {
+        $crate::solution!(@options $day, [$($generate)?] [$($alt)* $([$name, $part])*]; $($($rest)*)?);
+    };
+    (@options $day:expr, [$($generate:ident)?] [$($alt:tt)*];) => {
+        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2]; [$($generate)?] [$($alt)*]);
+    };
+
+    (@impl $day:expr, $( [$func:expr, $part:expr] )*; [$($generate:ident)?] [$( [$alt:ident, $alt_part:expr] )*]) => {
         /// The current day.
         const DAY: $crate::template::Day = $crate::day!($day);
 
@@ -59,10 +112,53 @@ macro_rules! solution {
         #[global_allocator]
         static ALLOC: dhat::Alloc = dhat::Alloc;
 
+        #[cfg(all(feature = "alloc-count", not(feature = "dhat-heap")))]
+        #[global_allocator]
+        static ALLOC: $crate::template::alloc::CountingAlloc = $crate::template::alloc::CountingAlloc;
+
         fn main() {
             use $crate::template::runner::*;
-            let input = $crate::template::read_file("inputs", DAY);
-            $( run_part($func, &input, DAY, $part); )*
+            let generator: Option<$crate::template::generator::Generator> = None $( .or(Some($generate)) )?;
+
+            let implementations = [
+                $( Implementation {
+                    part: $part,
+                    name: stringify!($func),
+                    alternative: false,
+                    func: |input: &str| $func(input).map(|result| result.to_string()),
+                }, )*
+                $( Implementation {
+                    part: $alt_part,
+                    name: stringify!($alt),
+                    alternative: true,
+                    func: |input: &str| $alt(input).map(|result| result.to_string()),
+                }, )*
+            ];
+
+            let args: Vec<String> = std::env::args().collect();
+            if let Some(options) = $crate::template::diff_test::Options::from_args(&args) {
+                $crate::template::diff_test::run(DAY, &implementations, generator, &options);
+                return;
+            }
+            if let Some(options) = $crate::template::fuzz::Options::from_args(&args) {
+                $crate::template::fuzz::run(DAY, &implementations, &options);
+                return;
+            }
+
+            let variants = select_variants(DAY, &implementations, &args);
+            let input = read_input(DAY, generator);
+
+            if variants.is_empty() {
+                $( run_part($func, &input, DAY, $part); )*
+            } else {
+                // NOTE: run the functions themselves, so the conversion to a string is not timed.
+                for implementation in variants {
+                    match implementation.name {
+                        $( stringify!($alt) => run_part($alt, input.as_str(), DAY, $alt_part), )*
+                        _ => unreachable!("variants are always alternatives"),
+                    }
+                }
+            }
         }
     };
 }
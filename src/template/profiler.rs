@@ -0,0 +1,135 @@
+/// Samples a solution part with an in-process, signal-based profiler and writes a flamegraph.
+/// Activated with the `profile` feature, see `cargo solve <day> --profile`.
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::hint::black_box;
+use std::io::{stdout, Write as _};
+use std::time::{Duration, Instant};
+
+use pprof::{ProfilerGuardBuilder, Report};
+
+use crate::template::{Day, ANSI_ITALIC, ANSI_RESET};
+
+static PROFILES_DIR: &str = "target/profiles";
+
+/// Samples per second. A prime, so sampling does not line up with periodic work.
+const SAMPLE_FREQUENCY: i32 = 997;
+
+/// A part is executed repeatedly until this much time has passed, so fast parts get enough samples.
+const PROFILE_DURATION: Duration = Duration::from_secs(3);
+
+/// Frames from these libraries are dropped, as recommended by `pprof`.
+const BLOCKLIST: [&str; 4] = ["libc", "libgcc", "pthread", "vdso"];
+
+#[allow(dead_code)]
+#[derive(Debug)]
+pub enum Error {
+    Profiler(pprof::Error),
+    IO(std::io::Error),
+}
+
+impl From<pprof::Error> for Error {
+    fn from(e: pprof::Error) -> Self {
+        Error::Profiler(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IO(e)
+    }
+}
+
+/// Profile a solution part and write `target/profiles/<day>-part<part>.{svg,folded}`.
+pub fn profile_part<I: Clone, T>(func: impl Fn(I) -> T, input: I, day: Day, part: u8) {
+    print!(" > {ANSI_ITALIC}profiling{ANSI_RESET}");
+    let _ = stdout().flush();
+
+    match run_profiler(func, input, day, part) {
+        Ok((samples, path)) => {
+            println!("\r{ANSI_ITALIC}Wrote {samples} samples to \"{path}.svg\"{ANSI_RESET}");
+        }
+        Err(e) => {
+            println!();
+            eprintln!("Failed to profile part {part}: {e:?}");
+        }
+    }
+}
+
+fn run_profiler<I: Clone, T>(
+    func: impl Fn(I) -> T,
+    input: I,
+    day: Day,
+    part: u8,
+) -> Result<(isize, String), Error> {
+    let guard = ProfilerGuardBuilder::default()
+        .frequency(SAMPLE_FREQUENCY)
+        .blocklist(&BLOCKLIST)
+        .build()?;
+
+    // NOTE: always run at least once, slow parts are not repeated.
+    let timer = Instant::now();
+    loop {
+        black_box(func(black_box(input.clone())));
+        if timer.elapsed() >= PROFILE_DURATION {
+            break;
+        }
+    }
+
+    let report = guard.report().build()?;
+    drop(guard);
+
+    fs::create_dir_all(PROFILES_DIR)?;
+    let path = format!("{PROFILES_DIR}/{day}-part{part}");
+
+    let stacks = collapse_stacks(folded_stacks(&report));
+    fs::write(format!("{path}.folded"), stacks.join("\n") + "\n")?;
+    report.flamegraph(File::create(format!("{path}.svg"))?)?;
+
+    Ok((report.data.values().sum(), path))
+}
+
+/// Render the samples of a report in the folded format used by `inferno` and `flamegraph.pl`.
+fn folded_stacks(report: &Report) -> impl Iterator<Item = (String, isize)> + '_ {
+    report.data.iter().map(|(frames, count)| {
+        let mut line = frames.thread_name_or_id();
+        for symbol in frames.frames.iter().rev().flat_map(|f| f.iter().rev()) {
+            let _ = write!(line, ";{symbol}");
+        }
+        (line, *count)
+    })
+}
+
+/// Merge identical stacks and sort them, so folded files of two runs can be diffed.
+fn collapse_stacks(stacks: impl Iterator<Item = (String, isize)>) -> Vec<String> {
+    let mut merged: BTreeMap<String, isize> = BTreeMap::new();
+    for (stack, count) in stacks {
+        *merged.entry(stack).or_default() += count;
+    }
+
+    merged
+        .into_iter()
+        .map(|(stack, count)| format!("{stack} {count}"))
+        .collect()
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::collapse_stacks;
+
+    #[test]
+    fn collapses_stacks() {
+        let stacks = [
+            ("main;solve;parse".to_string(), 2),
+            ("main;solve".to_string(), 1),
+            ("main;solve;parse".to_string(), 3),
+        ];
+
+        assert_eq!(
+            collapse_stacks(stacks.into_iter()),
+            vec!["main;solve 1", "main;solve;parse 5"]
+        );
+    }
+}
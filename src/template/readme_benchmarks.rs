@@ -2,6 +2,8 @@
 /// The approach taken is similar to how `aoc-readme-stars` handles this.
 use std::{fs, io};
 
+use crate::template::alloc::AllocStats;
+use crate::template::instructions;
 use crate::template::timings::Timings;
 use crate::template::Day;
 
@@ -21,8 +23,8 @@ impl From<std::io::Error> for Error {
 }
 
 pub struct TablePosition {
-    pos_start: usize,
-    pos_end: usize,
+    pub pos_start: usize,
+    pub pos_end: usize,
 }
 
 #[must_use]
@@ -30,8 +32,9 @@ pub fn get_path_for_bin(day: Day) -> String {
     format!("./src/bin/{day}.rs")
 }
 
-fn locate_table(readme: &str) -> Result<TablePosition, Error> {
-    let matches: Vec<_> = readme.match_indices(MARKER).collect();
+/// Locate the section between (and including) two occurences of `marker`.
+pub fn locate_table(readme: &str, marker: &str) -> Result<TablePosition, Error> {
+    let matches: Vec<_> = readme.match_indices(marker).collect();
 
     if matches.len() > 2 {
         return Err(Error::Parser(
@@ -52,26 +55,74 @@ fn locate_table(readme: &str) -> Result<TablePosition, Error> {
     Ok(TablePosition { pos_start, pos_end })
 }
 
+fn format_alloc_stats(stats: Option<AllocStats>) -> String {
+    stats.map_or_else(|| "-".into(), |s| format!("`{s}`"))
+}
+
+fn format_instructions(count: Option<u64>) -> String {
+    count.map_or_else(
+        || "-".into(),
+        |c| format!("`{}`", instructions::format_count(c)),
+    )
+}
+
 fn construct_table(prefix: &str, timings: Timings, total_millis: f64) -> String {
     let header = format!("{prefix} Benchmarks");
 
-    let mut lines: Vec<String> = vec![
-        MARKER.into(),
-        header,
-        String::new(),
-        "| Day | Part 1 | Part 2 |".into(),
-        "| :---: | :---: | :---:  |".into(),
-    ];
+    // NOTE: optional columns are only shown once a solution was benched with `--instructions` or `--alloc`.
+    let show_instructions = timings.has_instructions();
+    let show_allocs = timings.has_alloc_stats();
+
+    let mut lines: Vec<String> = vec![MARKER.into(), header, String::new()];
+
+    let mut columns = String::from("| Day | Part 1 | Part 2 |");
+    let mut alignment = String::from("| :---: | :---: | :---:  |");
+
+    if show_instructions {
+        columns.push_str(" Instructions |");
+        alignment.push_str(" :---: |");
+    }
+
+    if show_allocs {
+        columns.push_str(" Allocations |");
+        alignment.push_str(" :---: |");
+    }
+
+    lines.push(columns);
+    lines.push(alignment);
 
     for timing in timings.data {
         let path = get_path_for_bin(timing.day);
-        lines.push(format!(
-            "| [Day {}]({}) | `{}` | `{}` |",
+        let variant = timing
+            .variant
+            .map(|v| format!(" · `{v}`"))
+            .unwrap_or_default();
+        let mut line = format!(
+            "| [Day {}]({}){} | `{}` | `{}` |",
             timing.day.into_inner(),
             path,
+            variant,
             timing.part_1.unwrap_or_else(|| "-".into()),
             timing.part_2.unwrap_or_else(|| "-".into())
-        ));
+        );
+
+        if show_instructions {
+            line.push_str(&format!(
+                " {} / {} |",
+                format_instructions(timing.part_1_instructions),
+                format_instructions(timing.part_2_instructions)
+            ));
+        }
+
+        if show_allocs {
+            line.push_str(&format!(
+                " {} / {} |",
+                format_alloc_stats(timing.part_1_alloc),
+                format_alloc_stats(timing.part_2_alloc)
+            ));
+        }
+
+        lines.push(line);
     }
 
     lines.push(String::new());
@@ -82,7 +133,7 @@ fn construct_table(prefix: &str, timings: Timings, total_millis: f64) -> String
 }
 
 fn update_content(s: &mut String, timings: Timings, total_millis: f64) -> Result<(), Error> {
-    let positions = locate_table(s)?;
+    let positions = locate_table(s, MARKER)?;
     let table = construct_table("##", timings, total_millis);
     s.replace_range(positions.pos_start..positions.pos_end, &table);
     Ok(())
@@ -91,7 +142,7 @@ fn update_content(s: &mut String, timings: Timings, total_millis: f64) -> Result
 pub fn update(timings: Timings) -> Result<(), Error> {
     let path = "README.md";
     let mut readme = String::from_utf8_lossy(&fs::read(path)?).to_string();
-    let total_millis = timings.total_millis();
+    let total_millis = timings.main_total_millis();
     update_content(&mut readme, timings, total_millis)?;
     fs::write(path, &readme)?;
     Ok(())
@@ -101,27 +152,47 @@ pub fn update(timings: Timings) -> Result<(), Error> {
 #[cfg(test)]
 mod tests {
     use super::{update_content, MARKER};
-    use crate::{day, template::timings::Timing, template::timings::Timings};
+    use crate::{
+        day, template::alloc::AllocStats, template::timings::Timing, template::timings::Timings,
+    };
 
     fn get_mock_timings() -> Timings {
         Timings {
             data: vec![
                 Timing {
                     day: day!(1),
+                    variant: None,
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 3e+10,
                 },
                 Timing {
                     day: day!(2),
+                    variant: None,
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 7e+10,
                 },
                 Timing {
                     day: day!(4),
+                    variant: None,
                     part_1: Some("40ms".into()),
                     part_2: Some("50ms".into()),
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 9e+10,
                 },
             ],
@@ -181,4 +252,53 @@ mod tests {
         .join("\n");
         assert_eq!(s, expected);
     }
+
+    #[test]
+    fn format_benchmarks_with_alloc_stats() {
+        let mut timings = get_mock_timings();
+        timings.data[0].part_2_alloc = Some(AllocStats {
+            allocations: 3,
+            bytes: 2048,
+            peak_bytes: 1024,
+        });
+
+        let mut s = format!("{}\n{}", MARKER, MARKER);
+        update_content(&mut s, timings, 190.0).unwrap();
+
+        assert!(s.contains("| Day | Part 1 | Part 2 | Allocations |"));
+        assert!(s.contains(
+            "| [Day 1](./src/bin/01.rs) | `10ms` | `20ms` | - / `3 allocs · 2.00 KiB · peak 1.00 KiB` |"
+        ));
+        assert!(s.contains("| [Day 2](./src/bin/02.rs) | `30ms` | `40ms` | - / - |"));
+    }
+
+    #[test]
+    fn format_benchmarks_with_instructions() {
+        let mut timings = get_mock_timings();
+        timings.data[2].part_1_instructions = Some(1_234_567);
+
+        let mut s = format!("{}\n{}", MARKER, MARKER);
+        update_content(&mut s, timings, 190.0).unwrap();
+
+        assert!(s.contains("| Day | Part 1 | Part 2 | Instructions |"));
+        assert!(s.contains("| :---: | :---: | :---:  | :---: |"));
+        assert!(s.contains("| [Day 4](./src/bin/04.rs) | `40ms` | `50ms` | `1,234,567` / - |"));
+        assert!(s.contains("| [Day 1](./src/bin/01.rs) | `10ms` | `20ms` | - / - |"));
+    }
+
+    #[test]
+    fn format_benchmarks_with_variants() {
+        let mut timings = get_mock_timings();
+        let mut variant = timings.data[0].clone();
+        variant.variant = Some("naive".into());
+        variant.part_1 = None;
+        timings.data.insert(1, variant);
+
+        let mut s = format!("{}\n{}", MARKER, MARKER);
+        update_content(&mut s, timings, 190.0).unwrap();
+
+        assert!(s.contains(
+            "| [Day 1](./src/bin/01.rs) | `10ms` | `20ms` |\n| [Day 1](./src/bin/01.rs) · `naive` | `-` | `20ms` |"
+        ));
+    }
 }
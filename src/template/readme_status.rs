@@ -0,0 +1,141 @@
+/// Module that updates the readme with the progress of every day.
+/// Uses the same marker approach as the benchmark table.
+use std::fs;
+
+use crate::template::readme_benchmarks::{get_path_for_bin, locate_table, Error};
+use crate::template::status::DayStatus;
+
+static MARKER: &str = "<!--- status table --->";
+
+fn check(value: bool) -> &'static str {
+    if value {
+        "✔"
+    } else {
+        "-"
+    }
+}
+
+fn construct_table(prefix: &str, statuses: &[DayStatus]) -> String {
+    let header = format!("{prefix} Status");
+
+    let mut lines: Vec<String> = vec![
+        MARKER.into(),
+        header,
+        String::new(),
+        "| Day | Scaffolded | Example | Input | Part 1 | Part 2 | Runtime |".into(),
+        "| :---: | :---: | :---: | :---: | :---: | :---: | :---: |".into(),
+    ];
+
+    for status in statuses.iter().filter(|s| s.is_started()) {
+        let path = get_path_for_bin(status.day);
+        let runtime = status
+            .total_nanos
+            .map_or_else(|| "-".into(), |n| format!("`{:.2}ms`", n / 1_000_000_f64));
+
+        lines.push(format!(
+            "| [Day {}]({}) | {} | {} | {} | {} | {} | {} |",
+            status.day.into_inner(),
+            path,
+            check(status.scaffolded),
+            check(status.example),
+            check(status.input),
+            status.parts[0].label(),
+            status.parts[1].label(),
+            runtime,
+        ));
+    }
+
+    lines.push(MARKER.into());
+
+    lines.join("\n")
+}
+
+fn update_content(s: &mut String, statuses: &[DayStatus]) -> Result<(), Error> {
+    let positions = locate_table(s, MARKER)?;
+    let table = construct_table("##", statuses);
+    s.replace_range(positions.pos_start..positions.pos_end, &table);
+    Ok(())
+}
+
+pub fn update(statuses: &[DayStatus]) -> Result<(), Error> {
+    let path = "README.md";
+    let mut readme = String::from_utf8_lossy(&fs::read(path)?).to_string();
+    update_content(&mut readme, statuses)?;
+    fs::write(path, &readme)?;
+    Ok(())
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{update_content, MARKER};
+    use crate::{
+        day,
+        template::status::{DayStatus, PartStatus},
+    };
+
+    fn get_mock_statuses() -> Vec<DayStatus> {
+        vec![
+            DayStatus {
+                day: day!(1),
+                scaffolded: true,
+                example: true,
+                input: true,
+                parts: [PartStatus::Verified, PartStatus::Solving],
+                total_nanos: Some(1_500_000_f64),
+            },
+            DayStatus {
+                day: day!(2),
+                scaffolded: false,
+                example: false,
+                input: false,
+                parts: [PartStatus::Unknown, PartStatus::Unknown],
+                total_nanos: None,
+            },
+            DayStatus {
+                day: day!(3),
+                scaffolded: true,
+                example: false,
+                input: true,
+                parts: [PartStatus::Wrong, PartStatus::Unsolved],
+                total_nanos: None,
+            },
+        ]
+    }
+
+    #[test]
+    #[should_panic]
+    fn errors_if_marker_not_present() {
+        let mut s = "# readme".to_string();
+        update_content(&mut s, &get_mock_statuses()).unwrap();
+    }
+
+    #[test]
+    fn updates_existing_status() {
+        let mut s = format!("foo\n{}{}\nbaz", MARKER, MARKER);
+        update_content(&mut s, &get_mock_statuses()).unwrap();
+        update_content(&mut s, &get_mock_statuses()).unwrap();
+        assert_eq!(s.matches(MARKER).collect::<Vec<&str>>().len(), 2);
+        assert_eq!(s.matches("## Status").collect::<Vec<&str>>().len(), 1);
+    }
+
+    #[test]
+    fn format_status() {
+        let mut s = format!("foo\n{}\n{}\nbaz", MARKER, MARKER);
+        update_content(&mut s, &get_mock_statuses()).unwrap();
+        let expected = [
+            "foo",
+            "<!--- status table --->",
+            "## Status",
+            "",
+            "| Day | Scaffolded | Example | Input | Part 1 | Part 2 | Runtime |",
+            "| :---: | :---: | :---: | :---: | :---: | :---: | :---: |",
+            "| [Day 1](./src/bin/01.rs) | ✔ | ✔ | ✔ | ⭐ | ✔ | `1.50ms` |",
+            "| [Day 3](./src/bin/03.rs) | ✔ | - | ✔ | ✖ | - | - |",
+            "<!--- status table --->",
+            "baz",
+        ]
+        .join("\n");
+        assert_eq!(s, expected);
+    }
+}
@@ -1,13 +1,21 @@
 use std::{collections::HashSet, io};
 
-use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+use crate::template::{bench_config::BenchConfig, Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
 
 use super::{
     all_days,
     timings::{Timing, Timings},
 };
 
-pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -> Option<Timings> {
+pub fn run_multi(
+    days_to_run: &HashSet<Day>,
+    is_release: bool,
+    is_timed: bool,
+    count_allocs: bool,
+    count_instructions: bool,
+    bench: &BenchConfig,
+    variant: Option<&str>,
+) -> Option<Timings> {
     let mut timings: Vec<Timing> = Vec::with_capacity(days_to_run.len());
 
     let mut need_space = false;
@@ -24,12 +32,25 @@ pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -
             println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
             println!("------");
 
-            let output = child_commands::run_solution(day, is_timed, is_release).unwrap();
+            let output = child_commands::run_solution(
+                day,
+                is_timed,
+                is_release,
+                count_allocs,
+                count_instructions,
+                bench,
+                variant,
+            )
+            .unwrap();
 
             if output.is_empty() {
                 println!("Not solved.");
             } else {
-                let val = child_commands::parse_exec_time(&output, day);
+                let mut val = child_commands::parse_exec_time(&output, day);
+                val.variant = variant.map(String::from);
+                if is_timed {
+                    val.bench = Some(*bench);
+                }
                 timings.push(val);
             }
         });
@@ -68,7 +89,9 @@ pub fn get_path_for_bin(day: Day) -> String {
 /// This module encapsulates interaction with these binaries, both invoking them as well as parsing the timing output.
 pub mod child_commands {
     use super::{get_path_for_bin, Error};
-    use crate::template::Day;
+    use crate::template::{
+        alloc::AllocStats, bench_config::BenchConfig, generator, instructions, strip_ansi, Day,
+    };
     use std::{
         io::{BufRead, BufReader},
         path::Path,
@@ -77,7 +100,15 @@ pub mod child_commands {
     };
 
     /// Run the solution bin for a given day
-    pub fn run_solution(day: Day, is_timed: bool, is_release: bool) -> Result<Vec<String>, Error> {
+    pub fn run_solution(
+        day: Day,
+        is_timed: bool,
+        is_release: bool,
+        count_allocs: bool,
+        count_instructions: bool,
+        bench: &BenchConfig,
+        variant: Option<&str>,
+    ) -> Result<Vec<String>, Error> {
         // skip command invocation for days that have not been scaffolded yet.
         if !Path::new(&get_path_for_bin(day)).exists() {
             return Ok(vec![]);
@@ -90,10 +121,35 @@ pub mod child_commands {
             args.push("--release");
         }
 
+        let mut features = vec![];
+        if count_allocs {
+            features.push("alloc-count");
+        }
+        if count_instructions {
+            features.push("instructions");
+        }
+        let features = features.join(",");
+        if !features.is_empty() {
+            args.push("--features");
+            args.push(&features);
+        }
+
+        args.push("--");
+
+        let bench_args = bench.to_args();
         if is_timed {
-            // mirror `--time` flag to child invocations.
-            args.push("--");
+            // mirror `--time` flag and bench settings to child invocations.
             args.push("--time");
+            args.extend(bench_args.iter().map(String::as_str));
+        }
+
+        if count_instructions {
+            args.push("--instructions");
+        }
+
+        if let Some(variant) = variant {
+            args.push("--variant");
+            args.push(variant);
         }
 
         // spawn child command with piped stdout/stderr.
@@ -128,11 +184,138 @@ pub mod child_commands {
         Ok(output)
     }
 
+    /// Run the solution bin for a given day without forwarding its output.
+    pub fn capture_solution(day: Day, is_release: bool) -> Result<Vec<String>, Error> {
+        if !Path::new(&get_path_for_bin(day)).exists() {
+            return Ok(vec![]);
+        }
+
+        let day_padded = day.to_string();
+        let mut args = vec!["run", "--quiet", "--bin", &day_padded];
+
+        if is_release {
+            args.push("--release");
+        }
+
+        let output = Command::new("cargo")
+            .args(&args)
+            .stderr(Stdio::null())
+            .output()?;
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect())
+    }
+
+    /// Run the solution bin for a given day in release mode on a generated input and capture its output.
+    /// Errors of the bin (e.g. a missing generator) are forwarded to stderr.
+    pub fn run_generated(
+        day: Day,
+        seed: u64,
+        size: usize,
+        bench: &BenchConfig,
+    ) -> Result<Vec<String>, Error> {
+        let day_padded = day.to_string();
+        let mut args = vec![
+            "run".to_string(),
+            "--quiet".to_string(),
+            "--release".to_string(),
+            "--bin".to_string(),
+            day_padded,
+            "--".to_string(),
+            "--time".to_string(),
+        ];
+        args.extend(bench.to_args());
+        args.extend(generator::to_args(seed, size));
+
+        let output = Command::new("cargo")
+            .args(&args)
+            .stderr(Stdio::inherit())
+            .output()?;
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect())
+    }
+
+    /// Parse the average execution time of both parts in nanoseconds. Parts that were not benched are `None`.
+    pub fn parse_part_nanos(output: &[String]) -> [Option<f64>; 2] {
+        let mut nanos = [None, None];
+
+        for line in output {
+            let line = line.rsplit('\r').next().unwrap_or_default();
+            if !line.contains(" samples)") {
+                continue;
+            }
+
+            let idx = if line.starts_with("Part 1:") {
+                0
+            } else if line.starts_with("Part 2:") {
+                1
+            } else {
+                continue;
+            };
+
+            nanos[idx] = parse_time(line).map(|(_, nanos)| nanos);
+        }
+
+        nanos
+    }
+
+    /// Parse the printed result of both parts from the output of a solution bin.
+    /// Parts that did not run or returned `None` are `None`.
+    pub fn parse_results(output: &[String]) -> [Option<String>; 2] {
+        let mut results = [None, None];
+
+        // NOTE: intermediate results are overwritten with `\r`, only the last segment is relevant.
+        let lines: Vec<String> = output
+            .iter()
+            .map(|l| strip_ansi(l.rsplit('\r').next().unwrap_or_default()))
+            .collect();
+
+        for (i, line) in lines.iter().enumerate() {
+            let part = if let Some(rest) = line.strip_prefix("Part 1: ") {
+                Some((0, rest))
+            } else {
+                line.strip_prefix("Part 2: ").map(|rest| (1, rest))
+            };
+
+            let Some((idx, rest)) = part else {
+                continue;
+            };
+
+            results[idx] = if rest.starts_with('✖') {
+                None
+            } else if rest.starts_with('▼') {
+                // multi-line results are printed below the part line.
+                let value: Vec<&str> = lines[i + 1..]
+                    .iter()
+                    .take_while(|l| !l.starts_with("Part "))
+                    .map(String::as_str)
+                    .collect();
+                Some(value.join("\n").trim_end().to_string())
+            } else {
+                let value = rest.rfind(" (").map_or(rest, |pos| &rest[..pos]);
+                Some(value.trim().to_string())
+            };
+        }
+
+        results
+    }
+
     pub fn parse_exec_time(output: &[String], day: Day) -> super::Timing {
         let mut timings = super::Timing {
             day,
+            variant: None,
             part_1: None,
             part_2: None,
+            part_1_alloc: None,
+            part_2_alloc: None,
+            part_1_instructions: None,
+            part_2_instructions: None,
+            bench: None,
             total_nanos: 0_f64,
         };
 
@@ -149,13 +332,17 @@ pub mod child_commands {
                 };
 
                 let part = l.split(':').next()?;
-                Some((part, timing_str, nanos))
+                Some((part, timing_str, nanos, l))
             })
-            .for_each(|(part, timing_str, nanos)| {
+            .for_each(|(part, timing_str, nanos, line)| {
                 if part.contains("Part 1") {
                     timings.part_1 = Some(timing_str.into());
+                    timings.part_1_alloc = parse_alloc_stats(line);
+                    timings.part_1_instructions = parse_instructions(line);
                 } else if part.contains("Part 2") {
                     timings.part_2 = Some(timing_str.into());
+                    timings.part_2_alloc = parse_alloc_stats(line);
+                    timings.part_2_instructions = parse_instructions(line);
                 }
 
                 timings.total_nanos += nanos;
@@ -164,6 +351,24 @@ pub mod child_commands {
         timings
     }
 
+    /// Metrics are printed in brackets after the timing, e.g. `(1.2ms @ 10 samples) [1,234 instructions] [3 allocs · 12 B · peak 12 B]`.
+    fn parse_metrics(line: &str) -> impl Iterator<Item = &str> {
+        line.split(" samples)")
+            .nth(1)
+            .unwrap_or_default()
+            .split(" [")
+            .filter_map(|metric| metric.trim_end().strip_suffix(']'))
+    }
+
+    fn parse_alloc_stats(line: &str) -> Option<AllocStats> {
+        parse_metrics(line).find_map(AllocStats::parse)
+    }
+
+    fn parse_instructions(line: &str) -> Option<u64> {
+        parse_metrics(line)
+            .find_map(|m| instructions::parse_count(m.strip_suffix(" instructions")?))
+    }
+
     fn parse_to_float(s: &str, postfix: &str) -> Option<f64> {
         s.split(postfix).next()?.parse().ok()
     }
@@ -174,7 +379,7 @@ pub mod child_commands {
             .split(" samples)")
             .next()?
             .split('(')
-            .last()?
+            .next_back()?
             .split('@')
             .next()?
             .trim();
@@ -207,7 +412,7 @@ pub mod child_commands {
     #[cfg(feature = "test_lib")]
     #[cfg(test)]
     mod tests {
-        use super::parse_exec_time;
+        use super::{parse_exec_time, parse_part_nanos, parse_results};
 
         use crate::day;
 
@@ -241,6 +446,41 @@ pub mod child_commands {
             assert_eq!(res.part_2.unwrap(), "100ms");
         }
 
+        #[test]
+        fn parses_allocation_stats() {
+            let res = parse_exec_time(
+                &[
+                    "Part 1: 0 (74.13ns @ 100000 samples) [3 allocs · 2.00 KiB · peak 1.00 KiB]"
+                        .into(),
+                    "Part 2: 10 (74.13ms @ 99999 samples)".into(),
+                    "".into(),
+                ],
+                day!(1),
+            );
+            assert_eq!(res.part_1.unwrap(), "74.13ns");
+            let stats = res.part_1_alloc.unwrap();
+            assert_eq!(stats.allocations, 3);
+            assert_eq!(stats.bytes, 2048);
+            assert_eq!(stats.peak_bytes, 1024);
+            assert!(res.part_2_alloc.is_none());
+        }
+
+        #[test]
+        fn parses_instructions() {
+            let res = parse_exec_time(
+                &[
+                    "Part 1: 0 (74.13ns @ 100000 samples) [1,234,567 instructions] [3 allocs · 12 B · peak 12 B]".into(),
+                    "Part 2: 10 (74.13ms @ 99999 samples) [42 instructions]".into(),
+                    "".into(),
+                ],
+                day!(1),
+            );
+            assert_eq!(res.part_1_instructions, Some(1_234_567));
+            assert_eq!(res.part_1_alloc.unwrap().allocations, 3);
+            assert_eq!(res.part_2_instructions, Some(42));
+            assert!(res.part_2_alloc.is_none());
+        }
+
         #[test]
         fn parses_missing_parts() {
             let res = parse_exec_time(
@@ -255,5 +495,37 @@ pub mod child_commands {
             assert!(res.part_1.is_none());
             assert!(res.part_2.is_none());
         }
+
+        #[test]
+        fn parses_results() {
+            let res = parse_results(&[
+                "Part 1: \x1b[1m42\x1b[0m\rPart 1: \x1b[1m42\x1b[0m (1.2ms)".into(),
+                "Part 2: ✖\rPart 2: ✖             ".into(),
+            ]);
+            assert_eq!(res[0].as_deref(), Some("42"));
+            assert!(res[1].is_none());
+        }
+
+        #[test]
+        fn parses_multiline_results() {
+            let res = parse_results(&[
+                "Part 1: 7 (2s @ 5 samples)".into(),
+                "Part 2: ▼ (1.0ms)".into(),
+                "#..".into(),
+                ".#.".into(),
+            ]);
+            assert_eq!(res[0].as_deref(), Some("7"));
+            assert_eq!(res[1].as_deref(), Some("#..\n.#."));
+        }
+
+        #[test]
+        fn parses_part_nanos() {
+            let res = parse_part_nanos(&[
+                "Part 1: 42 > benching\rPart 1: 42 (1.5µs @ 100 samples)".into(),
+                "Part 2: ✖             ".into(),
+            ]);
+            assert_approx_eq!(res[0].unwrap(), 1500_f64);
+            assert!(res[1].is_none());
+        }
     }
 }
@@ -4,18 +4,156 @@ use std::hint::black_box;
 use std::io::{stdout, Write};
 use std::process::Output;
 use std::time::{Duration, Instant};
-use std::{cmp, env, process};
+use std::{env, process};
 
+use crate::template::alloc::{self, AllocStats};
+use crate::template::bench_config::BenchConfig;
+use crate::template::generator::{self, Generator};
+use crate::template::instructions;
+#[cfg(feature = "profile")]
+use crate::template::profiler;
+#[cfg(feature = "trace")]
+use crate::template::trace;
+#[cfg(feature = "visualize")]
+use crate::template::visualize;
 use crate::template::ANSI_BOLD;
-use crate::template::{aoc_cli, Day, ANSI_ITALIC, ANSI_RESET};
+use crate::template::{aoc_cli, read_file, Day, ANSI_ITALIC, ANSI_RESET};
+
+/// A named implementation of a part, with its result converted to a string.
+/// Used where implementations are handled generically, see [`solution!`](crate::solution).
+pub struct Implementation {
+    pub part: u8,
+    pub name: &'static str,
+    /// Registered via `alternatives = [...]` instead of being `part_one` / `part_two`.
+    pub alternative: bool,
+    pub func: fn(&str) -> Option<String>,
+}
+
+impl Implementation {
+    /// The name alternatives are selected by with `--variant`, e.g. `naive` for `part_two_naive`.
+    pub fn variant(&self) -> Option<&'static str> {
+        if !self.alternative {
+            return None;
+        }
+
+        let name = self.name;
+        Some(
+            name.strip_prefix("part_one_")
+                .or_else(|| name.strip_prefix("part_two_"))
+                .unwrap_or(name),
+        )
+    }
+}
+
+/// Find the alternative implementations selected with `--variant <name>`, at most one per part.
+/// The name is either the variant name (`naive`) or the full function name (`part_two_naive`).
+/// Empty if no variant was selected.
+pub fn select_variants<'a>(
+    day: Day,
+    implementations: &'a [Implementation],
+    args: &[String],
+) -> Vec<&'a Implementation> {
+    let Some(pos) = args.iter().position(|x| x == "--variant") else {
+        return vec![];
+    };
+    let Some(variant) = args.get(pos + 1) else {
+        eprintln!("Missing variant name. Format: cargo solve 1 --variant naive");
+        process::exit(1);
+    };
+
+    let mut selected: Vec<&Implementation> = vec![];
+    for implementation in implementations {
+        let matches = implementation.variant() == Some(variant.as_str())
+            || (implementation.alternative && implementation.name == variant);
+
+        if matches && !selected.iter().any(|i| i.part == implementation.part) {
+            selected.push(implementation);
+        }
+    }
+
+    if selected.is_empty() {
+        let mut available: Vec<&str> = implementations
+            .iter()
+            .filter_map(Implementation::variant)
+            .collect();
+        available.sort_unstable();
+        available.dedup();
+        eprintln!(
+            "Day {day} has no variant \"{variant}\". Available variants: {}",
+            if available.is_empty() {
+                "none".to_string()
+            } else {
+                available.join(", ")
+            }
+        );
+        process::exit(1);
+    }
+
+    selected
+}
+
+/// Read the puzzle input of a day, or generate it if the binary was started with `--generate` (see `cargo stress`).
+pub fn read_input(day: Day, generator: Option<Generator>) -> String {
+    let args: Vec<String> = env::args().collect();
+
+    let Some((seed, size)) = generator::from_args(&args) else {
+        return read_file("inputs", day);
+    };
+
+    match generator {
+        Some(generate) => generate(seed, size),
+        None => {
+            eprintln!(
+                "Day {day} does not have an input generator. Add `pub fn generate(seed: u64, size: usize) -> String` and use `solution!({}, generate)`.",
+                day.into_inner()
+            );
+            process::exit(1);
+        }
+    }
+}
 
 pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
+    // NOTE: when started by the cachegrind backend, only run the requested part once and print nothing.
+    if let Some(target) = instructions::cachegrind_part() {
+        if target == part {
+            black_box(func(input));
+        }
+        return;
+    }
+
+    #[cfg(feature = "trace")]
+    trace::set_context(day, part);
+
     let part_str = format!("Part {part}");
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+    let (result, duration, samples, alloc_stats) = run_timed(&func, input.clone(), |result| {
+        print_result(result, &part_str, "");
+    });
+
+    let mut duration_str = format_duration(&duration, samples);
+
+    if env::args().any(|x| x == "--instructions") {
+        if let Some(count) = instructions::count(&func, input.clone(), part) {
+            duration_str.push_str(&format!(
+                " [{} instructions]",
+                instructions::format_count(count)
+            ));
+        }
+    }
+
+    if let Some(stats) = alloc_stats {
+        duration_str.push_str(&format!(" [{stats}]"));
+    }
 
-    print_result(&result, &part_str, &format_duration(&duration, samples));
+    print_result(&result, &part_str, &duration_str);
+
+    #[cfg(feature = "visualize")]
+    visualize::finish(day, part);
+
+    #[cfg(feature = "profile")]
+    if env::args().any(|x| x == "--profile") {
+        profiler::profile_part(func, input, day, part);
+    }
 
     if let Some(result) = result {
         submit_result(result, day, part);
@@ -24,43 +162,61 @@ pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, d
 
 /// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
 ///  1. in debug, the function is executed once.
-///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
+///  2. in release, the function is benched (by default approx. 1 second of execution time or 10 samples, whatever take longer, see [`BenchConfig`].)
+///
+/// With the `alloc-count` feature, allocation statistics of the first execution are returned as well.
+/// Allocations are only counted during that execution, the bench samples run without the counting overhead.
 fn run_timed<I: Clone, T>(
     func: impl Fn(I) -> T,
     input: I,
     hook: impl Fn(&T),
-) -> (T, Duration, u128) {
+) -> (T, Duration, u128, Option<AllocStats>) {
+    let input_clone = input.clone();
+    // NOTE: DHAT replaces the counting allocator if both are enabled.
+    let count_allocs = cfg!(all(feature = "alloc-count", not(feature = "dhat-heap")));
+
+    if count_allocs {
+        alloc::start();
+    }
     let timer = Instant::now();
     let result = {
-        let input = input.clone();
-
         #[cfg(feature = "dhat-heap")]
         let _profiler = dhat::Profiler::new_heap();
 
-        func(input)
+        func(input_clone)
     };
 
     let base_time = timer.elapsed();
+    let alloc_stats = count_allocs.then(alloc::stop);
 
     hook(&result);
 
-    let run = if std::env::args().any(|x| x == "--time") {
-        bench(func, input, &base_time)
+    let args: Vec<String> = env::args().collect();
+    let run = if args.iter().any(|x| x == "--time") {
+        bench(func, input, &base_time, &BenchConfig::from_args(&args))
     } else {
         (base_time, 1)
     };
 
-    (result, run.0, run.1)
+    (result, run.0, run.1, alloc_stats)
 }
 
-fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (Duration, u128) {
+fn bench<I: Clone, T>(
+    func: impl Fn(I) -> T,
+    input: I,
+    base_time: &Duration,
+    config: &BenchConfig,
+) -> (Duration, u128) {
     let mut stdout = stdout();
 
     print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
     let _ = stdout.flush();
 
-    let bench_iterations =
-        (Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10)).clamp(10, 10000);
+    for _ in 0..config.warmup {
+        black_box(func(black_box(input.clone())));
+    }
+
+    let bench_iterations = config.samples(base_time);
 
     let mut timers: Vec<Duration> = vec![];
 
@@ -168,3 +324,67 @@ fn submit_result<T: Display>(
     println!("Submitting result via aoc-cli...");
     Some(aoc_cli::submit(day, part, &result.to_string()))
 }
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{select_variants, Implementation};
+    use crate::template::Day;
+
+    fn implementation(name: &'static str, alternative: bool) -> Implementation {
+        implementation_of(2, name, alternative)
+    }
+
+    fn implementation_of(part: u8, name: &'static str, alternative: bool) -> Implementation {
+        Implementation {
+            part,
+            name,
+            alternative,
+            func: |_| None,
+        }
+    }
+
+    #[test]
+    fn names_variants() {
+        assert_eq!(implementation("part_two", false).variant(), None);
+        assert_eq!(
+            implementation("part_two_naive", true).variant(),
+            Some("naive")
+        );
+        assert_eq!(
+            implementation("iterative", true).variant(),
+            Some("iterative")
+        );
+    }
+
+    #[test]
+    fn selects_variant_per_part() {
+        let implementations = [
+            implementation_of(1, "part_one", false),
+            implementation_of(2, "part_two", false),
+            implementation_of(1, "part_one_naive", true),
+            implementation_of(2, "part_two_naive", true),
+            implementation_of(2, "part_two_fast", true),
+        ];
+        let args = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let day = Day::new(1).unwrap();
+
+        let names = |args: &[String]| {
+            select_variants(day, &implementations, args)
+                .iter()
+                .map(|i| i.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(&args(&["--time"])), Vec::<&str>::new());
+        assert_eq!(
+            names(&args(&["--variant", "naive"])),
+            vec!["part_one_naive", "part_two_naive"]
+        );
+        assert_eq!(names(&args(&["--variant", "fast"])), vec!["part_two_fast"]);
+        assert_eq!(
+            names(&args(&["--variant", "part_two_naive"])),
+            vec!["part_two_naive"]
+        );
+    }
+}
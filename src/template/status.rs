@@ -0,0 +1,140 @@
+/// Module that collects the progress of every day of advent: which files exist, which parts solve and
+/// whether their results match the recorded answers.
+use std::{fs, path::Path};
+
+use crate::template::answers::Answers;
+use crate::template::run_multi::{child_commands, get_path_for_bin};
+use crate::template::timings::Timings;
+use crate::template::{all_days, Day};
+
+/// Progress of a single part of a day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartStatus {
+    /// The part was not run, and no timing is stored for it.
+    Unknown,
+    /// The part ran and returned `None`.
+    Unsolved,
+    /// The part returns a result, but there is no recorded answer to check it against.
+    Solving,
+    /// The part returns the recorded answer.
+    Verified,
+    /// The part returns a result that differs from the recorded answer.
+    Wrong,
+}
+
+impl PartStatus {
+    /// The character used in the terminal calendar.
+    pub fn symbol(self) -> char {
+        match self {
+            PartStatus::Unknown | PartStatus::Unsolved => '·',
+            PartStatus::Solving => '☆',
+            PartStatus::Verified => '★',
+            PartStatus::Wrong => '✖',
+        }
+    }
+
+    /// The cell content used in the readme table.
+    pub fn label(self) -> &'static str {
+        match self {
+            PartStatus::Unknown | PartStatus::Unsolved => "-",
+            PartStatus::Solving => "✔",
+            PartStatus::Verified => "⭐",
+            PartStatus::Wrong => "✖",
+        }
+    }
+}
+
+/// Progress of a single day.
+#[derive(Clone, Debug)]
+pub struct DayStatus {
+    pub day: Day,
+    pub scaffolded: bool,
+    pub example: bool,
+    pub input: bool,
+    pub parts: [PartStatus; 2],
+    pub total_nanos: Option<f64>,
+}
+
+impl DayStatus {
+    /// Whether there is anything to report for this day.
+    pub fn is_started(&self) -> bool {
+        self.scaffolded || self.example || self.input
+    }
+}
+
+/// Collect the status of all days.
+/// When `run` is set, scaffolded days are executed and their results checked against `data/answers.json`.
+/// Otherwise, whether a part solves is derived from the stored timings.
+pub fn collect(run: bool) -> Vec<DayStatus> {
+    let timings = Timings::read_from_file();
+    let answers = Answers::read_from_file();
+
+    all_days()
+        .map(|day| {
+            let scaffolded = Path::new(&get_path_for_bin(day)).exists();
+            let timing = timings.get(day);
+
+            let parts = if run && scaffolded {
+                let output = child_commands::capture_solution(day, true).unwrap_or_default();
+                let results = child_commands::parse_results(&output);
+                [
+                    check_part(results[0].as_deref(), answers.get(day, 1)),
+                    check_part(results[1].as_deref(), answers.get(day, 2)),
+                ]
+            } else {
+                let solving = |part: &Option<String>| {
+                    if part.is_some() {
+                        PartStatus::Solving
+                    } else {
+                        PartStatus::Unknown
+                    }
+                };
+                timing.map_or([PartStatus::Unknown; 2], |t| {
+                    [solving(&t.part_1), solving(&t.part_2)]
+                })
+            };
+
+            DayStatus {
+                day,
+                scaffolded,
+                example: has_example(day),
+                input: is_non_empty(&format!("data/inputs/{day}.txt")),
+                parts,
+                total_nanos: timing.map(|t| t.total_nanos).filter(|n| *n > 0.0),
+            }
+        })
+        .collect()
+}
+
+fn check_part(result: Option<&str>, answer: Option<&str>) -> PartStatus {
+    match (result, answer) {
+        (None, _) => PartStatus::Unsolved,
+        (Some(_), None) => PartStatus::Solving,
+        (Some(result), Some(answer)) if result == answer => PartStatus::Verified,
+        (Some(_), Some(_)) => PartStatus::Wrong,
+    }
+}
+
+fn is_non_empty(path: &str) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.len() > 0)
+}
+
+/// A day has an example if either `NN.txt` or a part-specific `NN-1.txt` is present.
+fn has_example(day: Day) -> bool {
+    is_non_empty(&format!("data/examples/{day}.txt"))
+        || is_non_empty(&format!("data/examples/{day}-1.txt"))
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{check_part, PartStatus};
+
+    #[test]
+    fn checks_parts_against_answers() {
+        assert_eq!(check_part(None, Some("42")), PartStatus::Unsolved);
+        assert_eq!(check_part(Some("42"), None), PartStatus::Solving);
+        assert_eq!(check_part(Some("42"), Some("42")), PartStatus::Verified);
+        assert_eq!(check_part(Some("41"), Some("42")), PartStatus::Wrong);
+    }
+}
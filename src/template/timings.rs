@@ -1,7 +1,7 @@
 use std::{collections::HashMap, fs, io::Error, str::FromStr};
 use tinyjson::JsonValue;
 
-use crate::template::Day;
+use crate::template::{alloc::AllocStats, bench_config::BenchConfig, Day};
 
 static TIMINGS_FILE_PATH: &str = "./data/timings.json";
 
@@ -9,8 +9,18 @@ static TIMINGS_FILE_PATH: &str = "./data/timings.json";
 #[derive(Clone, Debug)]
 pub struct Timing {
     pub day: Day,
+    /// Alternative implementation the timing was taken with, see `--variant`. `None` for the main implementations.
+    pub variant: Option<String>,
     pub part_1: Option<String>,
     pub part_2: Option<String>,
+    /// Allocation stats, only present if benched with `--alloc`.
+    pub part_1_alloc: Option<AllocStats>,
+    pub part_2_alloc: Option<AllocStats>,
+    /// Retired instructions, only present if benched with `--instructions`.
+    pub part_1_instructions: Option<u64>,
+    pub part_2_instructions: Option<u64>,
+    /// Bench settings the timing was taken with.
+    pub bench: Option<BenchConfig>,
     pub total_nanos: f64,
 }
 
@@ -38,20 +48,19 @@ impl Timings {
     }
 
     /// Merge two sets of timings, overwriting `self` with `other` if present.
+    /// Timings are matched by day and variant.
+    /// Allocation stats and instruction counts are always taken from `other` as well, as they may be outdated otherwise.
     pub fn merge(&self, new: &Self) -> Self {
-        let mut data: Vec<Timing> = vec![];
-
-        for timing in &new.data {
-            data.push(timing.clone());
-        }
+        let mut data: Vec<Timing> = new.data.clone();
 
         for timing in &self.data {
-            if !data.iter().any(|t| t.day == timing.day) {
+            if !data.iter().any(|t| t.is_same_run(timing)) {
                 data.push(timing.clone());
             }
         }
 
-        data.sort_unstable_by(|a, b| a.day.cmp(&b.day));
+        // NOTE: variants are listed below the main implementations of their day.
+        data.sort_unstable_by(|a, b| (a.day, &a.variant).cmp(&(b.day, &b.variant)));
         Timings { data }
     }
 
@@ -60,10 +69,43 @@ impl Timings {
         self.data.iter().map(|x| x.total_nanos).sum::<f64>() / 1_000_000_f64
     }
 
+    /// Sum up total duration of the main implementations as millis, i.e. without variants.
+    pub fn main_total_millis(&self) -> f64 {
+        self.data
+            .iter()
+            .filter(|x| x.variant.is_none())
+            .fold(0_f64, |acc, x| acc + x.total_nanos)
+            / 1_000_000_f64
+    }
+
+    /// Timing of the main implementations of a day.
+    pub fn get(&self, day: Day) -> Option<&Timing> {
+        self.data
+            .iter()
+            .find(|t| t.day == day && t.variant.is_none())
+    }
+
     pub fn is_day_complete(&self, day: Day) -> bool {
+        self.get(day)
+            .is_some_and(|t| t.part_1.is_some() && t.part_2.is_some())
+    }
+
+    pub fn has_alloc_stats(&self) -> bool {
+        self.data
+            .iter()
+            .any(|t| t.part_1_alloc.is_some() || t.part_2_alloc.is_some())
+    }
+
+    pub fn has_instructions(&self) -> bool {
         self.data
             .iter()
-            .any(|t| t.day == day && t.part_1.is_some() && t.part_2.is_some())
+            .any(|t| t.part_1_instructions.is_some() || t.part_2_instructions.is_some())
+    }
+}
+
+impl Timing {
+    fn is_same_run(&self, other: &Timing) -> bool {
+        self.day == other.day && self.variant == other.variant
     }
 }
 
@@ -114,6 +156,10 @@ impl From<&Timing> for JsonValue {
         map.insert("day".into(), JsonValue::String(value.day.to_string()));
         map.insert("total_nanos".into(), JsonValue::Number(value.total_nanos));
 
+        if let Some(variant) = &value.variant {
+            map.insert("variant".into(), JsonValue::String(variant.clone()));
+        }
+
         let part_1 = value.part_1.clone().map(JsonValue::String);
         let part_2 = value.part_2.clone().map(JsonValue::String);
 
@@ -133,6 +179,33 @@ impl From<&Timing> for JsonValue {
             },
         );
 
+        if let Some(stats) = &value.part_1_alloc {
+            map.insert("part_1_alloc".into(), JsonValue::from(stats));
+        }
+
+        if let Some(stats) = &value.part_2_alloc {
+            map.insert("part_2_alloc".into(), JsonValue::from(stats));
+        }
+
+        if let Some(bench) = &value.bench {
+            map.insert("bench".into(), JsonValue::from(bench));
+        }
+
+        // NOTE: JSON numbers are floats, counts are stored as strings to not lose precision.
+        if let Some(count) = value.part_1_instructions {
+            map.insert(
+                "part_1_instructions".into(),
+                JsonValue::String(count.to_string()),
+            );
+        }
+
+        if let Some(count) = value.part_2_instructions {
+            map.insert(
+                "part_2_instructions".into(),
+                JsonValue::String(count.to_string()),
+            );
+        }
+
         JsonValue::Object(map)
     }
 }
@@ -151,6 +224,15 @@ impl TryFrom<&JsonValue> for Timing {
             .and_then(|day| Day::from_str(day).ok())
             .ok_or("Expected timing.day to be a Day struct.")?;
 
+        let variant = json
+            .get("variant")
+            .map(|v| {
+                v.get::<String>()
+                    .cloned()
+                    .ok_or("Expected timing.variant to be a string.")
+            })
+            .transpose()?;
+
         let part_1 = json
             .get("part_1")
             .map(|v| if v.is_null() { None } else { v.get::<String>() })
@@ -166,10 +248,39 @@ impl TryFrom<&JsonValue> for Timing {
             .and_then(|v| v.get::<f64>().copied())
             .ok_or("Expected timing.total_nanos to be a number.")?;
 
+        // NOTE: allocation stats are optional to stay compatible with older timing files.
+        let part_1_alloc = json
+            .get("part_1_alloc")
+            .map(AllocStats::try_from)
+            .transpose()?;
+
+        let part_2_alloc = json
+            .get("part_2_alloc")
+            .map(AllocStats::try_from)
+            .transpose()?;
+
+        let bench = json.get("bench").map(BenchConfig::try_from).transpose()?;
+
+        let instructions = |key: &str| {
+            json.get(key)
+                .map(|v| {
+                    v.get::<String>()
+                        .and_then(|s| s.parse::<u64>().ok())
+                        .ok_or(format!("Expected timing.{key} to be a numeric string."))
+                })
+                .transpose()
+        };
+
         Ok(Timing {
             day,
+            variant,
             part_1: part_1.cloned(),
             part_2: part_2.cloned(),
+            part_1_alloc,
+            part_2_alloc,
+            part_1_instructions: instructions("part_1_instructions")?,
+            part_2_instructions: instructions("part_2_instructions")?,
+            bench,
             total_nanos,
         })
     }
@@ -177,6 +288,51 @@ impl TryFrom<&JsonValue> for Timing {
 
 /* -------------------------------------------------------------------------- */
 
+impl From<&AllocStats> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &AllocStats) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        map.insert(
+            "allocations".into(),
+            JsonValue::Number(value.allocations as f64),
+        );
+        map.insert("bytes".into(), JsonValue::Number(value.bytes as f64));
+        map.insert(
+            "peak_bytes".into(),
+            JsonValue::Number(value.peak_bytes as f64),
+        );
+
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for AllocStats {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected allocation stats to be a JSON object.")?;
+
+        let field = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<f64>().copied())
+                .map(|v| v as usize)
+                .ok_or(format!("Expected alloc.{key} to be a number."))
+        };
+
+        Ok(AllocStats {
+            allocations: field("allocations")?,
+            bytes: field("bytes")?,
+            peak_bytes: field("peak_bytes")?,
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
 #[cfg(feature = "test_lib")]
 #[cfg(test)]
 mod tests {
@@ -189,20 +345,38 @@ mod tests {
             data: vec![
                 Timing {
                     day: day!(1),
+                    variant: None,
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 3e+10,
                 },
                 Timing {
                     day: day!(2),
+                    variant: None,
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 7e+10,
                 },
                 Timing {
                     day: day!(4),
+                    variant: None,
                     part_1: Some("40ms".into()),
                     part_2: None,
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 4e+10,
                 },
             ],
@@ -211,7 +385,10 @@ mod tests {
 
     #[cfg(test)]
     mod deserialization {
-        use crate::{day, template::timings::Timings};
+        use crate::{
+            day,
+            template::{bench_config::BenchConfig, timings::Timings},
+        };
 
         #[test]
         fn handles_json_timings() {
@@ -225,6 +402,44 @@ mod tests {
             assert_eq!(timing.total_nanos, 1_000_000_000_f64);
         }
 
+        #[test]
+        fn handles_json_alloc_stats() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "1ms", "part_2": null, "total_nanos": 1000000000, "part_1_alloc": { "allocations": 3, "bytes": 2048, "peak_bytes": 1024 } }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let timing = timings.data.first().unwrap();
+            let stats = timing.part_1_alloc.unwrap();
+            assert_eq!(stats.allocations, 3);
+            assert_eq!(stats.bytes, 2048);
+            assert_eq!(stats.peak_bytes, 1024);
+            assert!(timing.part_2_alloc.is_none());
+        }
+
+        #[test]
+        fn handles_json_instructions() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "1ms", "part_2": "2ms", "total_nanos": 3000000, "part_2_instructions": "1234567" }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let timing = timings.data.first().unwrap();
+            assert_eq!(timing.part_1_instructions, None);
+            assert_eq!(timing.part_2_instructions, Some(1_234_567));
+        }
+
+        #[test]
+        fn handles_json_bench_settings() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "1ms", "part_2": null, "total_nanos": 1000000, "bench": { "target_time_ms": 100, "min_samples": 3, "max_samples": 1000, "warmup": 0 } }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let bench = timings.data.first().unwrap().bench.unwrap();
+            assert_eq!(bench, BenchConfig::profile("quick").unwrap());
+        }
+
+        #[test]
+        fn handles_json_variants() {
+            let json = r#"{ "data": [{ "day": "06", "variant": "naive", "part_1": null, "part_2": "1s", "total_nanos": 1000000000 }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let timing = timings.data.first().unwrap();
+            assert_eq!(timing.variant.as_deref(), Some("naive"));
+            assert!(timings.get(day!(6)).is_none());
+        }
+
         #[test]
         fn handles_empty_timings() {
             let json = r#"{ "data": [] }"#.to_string();
@@ -283,8 +498,14 @@ mod tests {
             let timings = Timings {
                 data: vec![Timing {
                     day: day!(1),
+                    variant: None,
                     part_1: Some("1ms".into()),
                     part_2: Some("2ms".into()),
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 3_000_000_000_f64,
                 }],
             };
@@ -297,8 +518,14 @@ mod tests {
             let timings = Timings {
                 data: vec![Timing {
                     day: day!(1),
+                    variant: None,
                     part_1: Some("1ms".into()),
                     part_2: None,
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 1_000_000_000_f64,
                 }],
             };
@@ -311,8 +538,14 @@ mod tests {
             let timings = Timings {
                 data: vec![Timing {
                     day: day!(1),
+                    variant: None,
                     part_1: None,
                     part_2: None,
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 0.0,
                 }],
             };
@@ -325,7 +558,10 @@ mod tests {
     mod merge {
         use crate::{
             day,
-            template::timings::{Timing, Timings},
+            template::{
+                alloc::AllocStats,
+                timings::{Timing, Timings},
+            },
         };
 
         use super::get_mock_timings;
@@ -336,8 +572,14 @@ mod tests {
             let other = Timings {
                 data: vec![Timing {
                     day: day!(3),
+                    variant: None,
                     part_1: None,
                     part_2: None,
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 0_f64,
                 }],
             };
@@ -356,8 +598,14 @@ mod tests {
             let other = Timings {
                 data: vec![Timing {
                     day: day!(2),
+                    variant: None,
                     part_1: None,
                     part_2: None,
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
                     total_nanos: 0_f64,
                 }],
             };
@@ -378,6 +626,81 @@ mod tests {
             assert_eq!(merged.data.len(), 3);
         }
 
+        #[test]
+        fn drops_outdated_alloc_stats() {
+            let stats = AllocStats {
+                allocations: 1,
+                bytes: 8,
+                peak_bytes: 8,
+            };
+
+            let mut timings = get_mock_timings();
+            timings.data[1].part_1_alloc = Some(stats);
+
+            let other = Timings {
+                data: vec![Timing {
+                    day: day!(2),
+                    variant: None,
+                    part_1: Some("1ms".into()),
+                    part_2: None,
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
+                    total_nanos: 0_f64,
+                }],
+            };
+            let merged = timings.merge(&other);
+
+            assert_eq!(merged.data[1].part_1, Some("1ms".into()));
+            assert_eq!(merged.data[1].part_1_alloc, None);
+        }
+
+        #[test]
+        fn drops_outdated_instructions() {
+            let mut timings = get_mock_timings();
+            timings.data[1].part_1_instructions = Some(1_234);
+
+            let mut other = get_mock_timings();
+            other.data.truncate(2);
+            other.data[1].part_1_instructions = None;
+            other.data[1].part_2_instructions = Some(42);
+
+            let merged = timings.merge(&other);
+            assert_eq!(merged.data[1].part_1_instructions, None);
+            assert_eq!(merged.data[1].part_2_instructions, Some(42));
+        }
+
+        #[test]
+        fn keeps_variants_apart() {
+            let timings = get_mock_timings();
+            let other = Timings {
+                data: vec![Timing {
+                    day: day!(2),
+                    variant: Some("naive".into()),
+                    part_1: None,
+                    part_2: Some("1s".into()),
+                    part_1_alloc: None,
+                    part_2_alloc: None,
+                    part_1_instructions: None,
+                    part_2_instructions: None,
+                    bench: None,
+                    total_nanos: 1e+9,
+                }],
+            };
+            let merged = timings.merge(&other);
+
+            assert_eq!(merged.data.len(), 4);
+            assert_eq!(merged.data[1].variant, None);
+            assert_eq!(merged.data[2].variant.as_deref(), Some("naive"));
+            assert_eq!(merged.data[3].day, day!(4));
+            assert_eq!(merged.main_total_millis(), timings.total_millis());
+
+            let merged = merged.merge(&other);
+            assert_eq!(merged.data.len(), 4);
+        }
+
         #[test]
         fn handles_empty_other_timings() {
             let timings = get_mock_timings();
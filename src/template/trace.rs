@@ -0,0 +1,97 @@
+/// Debug output for solutions, see [`trace!`](crate::trace).
+/// Output is written to stderr, so it never interferes with the results and timings printed to stdout.
+use std::fmt::Arguments;
+use std::sync::atomic::{AtomicU8, Ordering::Relaxed};
+
+use crate::template::{Day, ANSI_ITALIC, ANSI_RESET};
+
+static CURRENT_DAY: AtomicU8 = AtomicU8::new(0);
+static CURRENT_PART: AtomicU8 = AtomicU8::new(0);
+
+/// Set the day and part that trace output is labelled with.
+pub fn set_context(day: Day, part: u8) {
+    CURRENT_DAY.store(day.into_inner(), Relaxed);
+    CURRENT_PART.store(part, Relaxed);
+}
+
+#[doc(hidden)]
+pub fn print(args: Arguments) {
+    eprintln!(
+        "{}",
+        format_trace(CURRENT_DAY.load(Relaxed), CURRENT_PART.load(Relaxed), args)
+    );
+}
+
+fn format_trace(day: u8, part: u8, args: Arguments) -> String {
+    let label = if day == 0 {
+        "[trace]".to_string()
+    } else if part == 0 {
+        format!("[{day:02}]")
+    } else {
+        format!("[{day:02}/{part}]")
+    };
+
+    // NOTE: label every line, so multi-line output (e.g. grids) stays attributable.
+    args.to_string()
+        .lines()
+        .map(|line| format!("{ANSI_ITALIC}{label}{ANSI_RESET} {line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Prints debug output labelled with the current day and part, e.g. `[06/2] visited 41 cells`.
+///
+/// Output is only printed when running a solution with `cargo solve <day> --trace`.
+/// In all other builds, including `cargo time`, the macro compiles to nothing.
+#[cfg(feature = "trace")]
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        $crate::template::trace::print(format_args!($($arg)*))
+    };
+}
+
+/// Prints debug output labelled with the current day and part, e.g. `[06/2] visited 41 cells`.
+///
+/// Output is only printed when running a solution with `cargo solve <day> --trace`.
+/// In all other builds, including `cargo time`, the macro compiles to nothing.
+#[cfg(not(feature = "trace"))]
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        // NOTE: arguments are still type-checked, so variables used only for tracing do not cause warnings.
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::format_trace;
+
+    #[test]
+    fn labels_output() {
+        assert_eq!(
+            format_trace(6, 2, format_args!("visited {} cells", 41)),
+            "\x1b[3m[06/2]\x1b[0m visited 41 cells"
+        );
+    }
+
+    #[test]
+    fn labels_every_line() {
+        assert_eq!(
+            format_trace(4, 0, format_args!("#.\n.#")),
+            "\x1b[3m[04]\x1b[0m #.\n\x1b[3m[04]\x1b[0m .#"
+        );
+    }
+
+    #[test]
+    fn labels_output_without_context() {
+        assert_eq!(
+            format_trace(0, 0, format_args!("foo")),
+            "\x1b[3m[trace]\x1b[0m foo"
+        );
+    }
+}
@@ -0,0 +1,509 @@
+/// Frames emitted by solutions with [`visualize!`](crate::visualize), played back by `cargo solve <day> --visualize`.
+/// Frames can be exported as an [asciinema](https://asciinema.org) cast or, with the `visualize` feature, as an animated GIF.
+use std::fmt::Write as _;
+use std::io::{stdout, Write};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use tinyjson::JsonValue;
+
+use crate::template::{Day, ANSI_ITALIC, ANSI_RESET};
+
+static VISUALIZATIONS_DIR: &str = "target/visualizations";
+
+const DEFAULT_FPS: u32 = 10;
+
+/// Recording stops after this many frames, so long simulations do not exhaust memory.
+const MAX_FRAMES: usize = 10_000;
+
+static FRAMES: Mutex<Vec<Frame>> = Mutex::new(Vec::new());
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// Colors used for cells without an explicit color.
+const BACKGROUND: Color = Color::rgb(15, 15, 35);
+const FOREGROUND: Color = Color::rgb(204, 204, 204);
+const PALETTE: [Color; 6] = [
+    Color::rgb(255, 255, 102),
+    Color::rgb(0, 153, 0),
+    Color::rgb(255, 102, 102),
+    Color::rgb(102, 178, 255),
+    Color::rgb(255, 178, 102),
+    Color::rgb(204, 153, 255),
+];
+
+/// A grid of characters with optional colors, plus a caption.
+/// Cells are addressed by row and column, like `grid[row][col]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<char>,
+    colors: Vec<Option<Color>>,
+    caption: String,
+}
+
+impl Frame {
+    /// Create an empty frame, filled with spaces.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![' '; width * height],
+            colors: vec![None; width * height],
+            caption: String::new(),
+        }
+    }
+
+    /// Create a frame from lines of text. Shorter lines are padded with spaces.
+    pub fn from_text(text: &str) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+        let mut frame = Self::new(width, lines.len());
+        for (row, line) in lines.iter().enumerate() {
+            for (col, ch) in line.chars().enumerate() {
+                frame.set(row, col, ch);
+            }
+        }
+
+        frame
+    }
+
+    /// Create a frame from a grid, mapping each cell to a character.
+    pub fn from_grid<T>(grid: &[Vec<T>], to_char: impl Fn(&T) -> char) -> Self {
+        let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut frame = Self::new(width, grid.len());
+        for (row, cells) in grid.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                frame.set(row, col, to_char(cell));
+            }
+        }
+
+        frame
+    }
+
+    #[must_use]
+    pub fn with_caption(mut self, caption: impl Into<String>) -> Self {
+        self.caption = caption.into();
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn caption(&self) -> &str {
+        &self.caption
+    }
+
+    /// Set the character of a cell. Out-of-bounds cells are ignored.
+    pub fn set(&mut self, row: usize, col: usize, ch: char) {
+        if let Some(idx) = self.index(row, col) {
+            self.cells[idx] = ch;
+        }
+    }
+
+    /// Set the color of a cell. Out-of-bounds cells are ignored.
+    pub fn set_color(&mut self, row: usize, col: usize, color: Color) {
+        if let Some(idx) = self.index(row, col) {
+            self.colors[idx] = Some(color);
+        }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<char> {
+        self.index(row, col).map(|idx| self.cells[idx])
+    }
+
+    fn index(&self, row: usize, col: usize) -> Option<usize> {
+        (row < self.height && col < self.width).then_some(row * self.width + col)
+    }
+
+    /// The color a cell is drawn with: its explicit color, or one derived from its character.
+    pub fn color(&self, row: usize, col: usize) -> Color {
+        let Some(idx) = self.index(row, col) else {
+            return BACKGROUND;
+        };
+
+        self.colors[idx].unwrap_or_else(|| match self.cells[idx] {
+            ' ' | '.' => BACKGROUND,
+            '#' => FOREGROUND,
+            ch => PALETTE[ch as usize % PALETTE.len()],
+        })
+    }
+
+    /// Render the frame for a terminal, with explicit colors as ANSI true color sequences.
+    pub fn render(&self) -> String {
+        let mut out = String::with_capacity(self.cells.len() * 2);
+
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = row * self.width + col;
+                match self.colors[idx] {
+                    Some(Color { r, g, b }) => {
+                        let _ =
+                            write!(out, "\x1b[38;2;{r};{g};{b}m{}{ANSI_RESET}", self.cells[idx]);
+                    }
+                    None => out.push(self.cells[idx]),
+                }
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+/// Record a frame. Use the [`visualize!`](crate::visualize) macro instead of calling this directly.
+#[doc(hidden)]
+pub fn record(frame: Frame) {
+    let mut frames = FRAMES.lock().unwrap();
+    if frames.len() < MAX_FRAMES {
+        frames.push(frame);
+    }
+}
+
+/// Take all frames recorded so far.
+pub fn take_frames() -> Vec<Frame> {
+    std::mem::take(&mut *FRAMES.lock().unwrap())
+}
+
+/// Emits a [`Frame`] for `cargo solve <day> --visualize`.
+///
+/// The frame expression is only evaluated in builds with the `visualize` feature,
+/// in all other builds, including `cargo time`, the macro compiles to nothing.
+#[cfg(feature = "visualize")]
+#[macro_export]
+macro_rules! visualize {
+    ($frame:expr) => {
+        $crate::template::visualize::record($frame)
+    };
+}
+
+/// Emits a [`Frame`] for `cargo solve <day> --visualize`.
+///
+/// The frame expression is only evaluated in builds with the `visualize` feature,
+/// in all other builds, including `cargo time`, the macro compiles to nothing.
+#[cfg(not(feature = "visualize"))]
+#[macro_export]
+macro_rules! visualize {
+    ($frame:expr) => {
+        // NOTE: the expression is still type-checked, so helpers used only for frames do not cause warnings.
+        if false {
+            let _: $crate::template::visualize::Frame = $frame;
+        }
+    };
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Playback settings, read from the arguments passed to a solution binary.
+pub struct Options {
+    pub fps: u32,
+    pub export: Option<String>,
+}
+
+impl Options {
+    pub fn from_args(args: &[String]) -> Self {
+        let value = |flag: &str| {
+            let pos = args.iter().position(|x| x == flag)?;
+            args.get(pos + 1).cloned()
+        };
+
+        Self {
+            fps: value("--fps")
+                .and_then(|fps| fps.parse().ok())
+                .filter(|fps| *fps > 0)
+                .unwrap_or(DEFAULT_FPS),
+            export: value("--export"),
+        }
+    }
+}
+
+/// Play or export the frames recorded while running a part.
+pub fn finish(day: Day, part: u8) {
+    let frames = take_frames();
+    if frames.is_empty() {
+        return;
+    }
+
+    let args: Vec<String> = std::env::args().collect();
+    let options = Options::from_args(&args);
+
+    match options.export.as_deref() {
+        None => play(&frames, options.fps),
+        Some(format) => match export(&frames, options.fps, format, day, part) {
+            Ok(path) => println!(
+                "{ANSI_ITALIC}Wrote {} frames to \"{path}\"{ANSI_RESET}",
+                frames.len()
+            ),
+            Err(e) => eprintln!("Failed to export frames: {e}"),
+        },
+    }
+}
+
+fn export(frames: &[Frame], fps: u32, format: &str, day: Day, part: u8) -> Result<String, String> {
+    std::fs::create_dir_all(VISUALIZATIONS_DIR).map_err(|e| e.to_string())?;
+    let path = format!("{VISUALIZATIONS_DIR}/{day}-part{part}.{format}");
+
+    match format {
+        "cast" => std::fs::write(&path, to_cast(frames, fps)).map_err(|e| e.to_string())?,
+        #[cfg(feature = "visualize")]
+        "gif" => {
+            let file = std::fs::File::create(&path).map_err(|e| e.to_string())?;
+            to_gif(frames, fps, std::io::BufWriter::new(file)).map_err(|e| e.to_string())?;
+        }
+        _ => {
+            return Err(format!(
+                "unknown format \"{format}\", expected \"cast\" or \"gif\"."
+            ))
+        }
+    }
+
+    Ok(path)
+}
+
+/// Play frames in the terminal below the current output, redrawing in place.
+pub fn play(frames: &[Frame], fps: u32) {
+    let mut stdout = stdout();
+    let delay = Duration::from_secs(1) / fps;
+
+    // hide cursor.
+    print!("\x1b[?25l");
+
+    let mut previous_height = None;
+
+    for (i, frame) in frames.iter().enumerate() {
+        // move to the start of the previous frame and clear everything below.
+        if let Some(height) = previous_height {
+            print!("\x1b[{height}F\x1b[J");
+        }
+        previous_height = Some(frame.height() + 1);
+
+        print!("{}", frame.render());
+        println!(
+            "\x1b[K{ANSI_ITALIC}frame {}/{}{ANSI_RESET} {}",
+            i + 1,
+            frames.len(),
+            frame.caption()
+        );
+        let _ = stdout.flush();
+        thread::sleep(delay);
+    }
+
+    // show cursor again.
+    print!("\x1b[?25h");
+    let _ = stdout.flush();
+}
+
+/// Render frames as an asciinema cast (v2), see <https://docs.asciinema.org/manual/asciicast/v2/>.
+#[allow(clippy::cast_precision_loss)]
+pub fn to_cast(frames: &[Frame], fps: u32) -> String {
+    let width = frames.iter().map(Frame::width).max().unwrap_or(0);
+    let height = frames.iter().map(Frame::height).max().unwrap_or(0);
+
+    let mut header = std::collections::HashMap::new();
+    header.insert("version".to_string(), JsonValue::Number(2.0));
+    header.insert("width".to_string(), JsonValue::Number(width.max(1) as f64));
+    // NOTE: one extra line for the caption.
+    header.insert("height".to_string(), JsonValue::Number((height + 1) as f64));
+
+    let mut lines = vec![JsonValue::Object(header).stringify().unwrap()];
+
+    for (i, frame) in frames.iter().enumerate() {
+        let output = format!(
+            "\x1b[H\x1b[2J{}{}",
+            frame.render().replace('\n', "\r\n"),
+            frame.caption()
+        );
+        let event = JsonValue::Array(vec![
+            JsonValue::Number(i as f64 / f64::from(fps)),
+            JsonValue::String("o".into()),
+            JsonValue::String(output),
+        ]);
+        lines.push(event.stringify().unwrap());
+    }
+
+    lines.join("\n") + "\n"
+}
+
+#[cfg(feature = "visualize")]
+pub use gif_export::to_gif;
+
+#[cfg(feature = "visualize")]
+mod gif_export {
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    use gif::{Encoder, EncodingError, Frame as GifFrame, Repeat};
+
+    use super::{Color, Frame};
+
+    /// Size of a cell in pixels.
+    const CELL_SIZE: usize = 4;
+
+    /// Write frames as an animated GIF. Each cell is drawn as a square in its color, captions are not included.
+    pub fn to_gif(frames: &[Frame], fps: u32, writer: impl Write) -> Result<(), EncodingError> {
+        let width = frames.iter().map(Frame::width).max().unwrap_or(0).max(1);
+        let height = frames.iter().map(Frame::height).max().unwrap_or(0).max(1);
+
+        let (px_width, px_height) = (
+            u16::try_from(width * CELL_SIZE).unwrap_or(u16::MAX),
+            u16::try_from(height * CELL_SIZE).unwrap_or(u16::MAX),
+        );
+
+        let palette = Palette::from_frames(frames);
+
+        let mut encoder = Encoder::new(writer, px_width, px_height, &palette.to_rgb())?;
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        // NOTE: GIF delays are in hundredths of a second, browsers clamp delays below 2.
+        let delay = u16::try_from((100 / fps).max(2)).unwrap_or(u16::MAX);
+
+        for frame in frames {
+            let mut pixels = vec![0_u8; usize::from(px_width) * usize::from(px_height)];
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                let (row, col) = (
+                    i / usize::from(px_width) / CELL_SIZE,
+                    i % usize::from(px_width) / CELL_SIZE,
+                );
+                *pixel = palette.index(frame.color(row, col));
+            }
+
+            let mut gif_frame = GifFrame::from_indexed_pixels(px_width, px_height, pixels, None);
+            gif_frame.delay = delay;
+            encoder.write_frame(&gif_frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// Up to 256 colors. Further colors are mapped to the closest color in the palette.
+    struct Palette {
+        colors: Vec<Color>,
+        indices: HashMap<Color, u8>,
+    }
+
+    impl Palette {
+        fn from_frames(frames: &[Frame]) -> Self {
+            let mut palette = Palette {
+                colors: vec![super::BACKGROUND],
+                indices: HashMap::from([(super::BACKGROUND, 0)]),
+            };
+
+            for frame in frames {
+                for row in 0..frame.height() {
+                    for col in 0..frame.width() {
+                        let color = frame.color(row, col);
+                        if palette.colors.len() < 256 && !palette.indices.contains_key(&color) {
+                            let idx = u8::try_from(palette.colors.len()).unwrap_or(u8::MAX);
+                            palette.indices.insert(color, idx);
+                            palette.colors.push(color);
+                        }
+                    }
+                }
+            }
+
+            palette
+        }
+
+        fn index(&self, color: Color) -> u8 {
+            if let Some(idx) = self.indices.get(&color) {
+                return *idx;
+            }
+
+            let distance = |c: &Color| {
+                let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+                d(c.r, color.r) + d(c.g, color.g) + d(c.b, color.b)
+            };
+
+            self.colors
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, c)| distance(c))
+                .and_then(|(idx, _)| u8::try_from(idx).ok())
+                .unwrap_or(0)
+        }
+
+        fn to_rgb(&self) -> Vec<u8> {
+            self.colors.iter().flat_map(|c| [c.r, c.g, c.b]).collect()
+        }
+    }
+}
+
+#[cfg(feature = "test_lib")]
+#[cfg(test)]
+mod tests {
+    use super::{to_cast, Color, Frame, Options};
+
+    #[test]
+    fn creates_frames_from_text() {
+        let frame = Frame::from_text("#.\n.#.");
+        assert_eq!(frame.width(), 3);
+        assert_eq!(frame.height(), 2);
+        assert_eq!(frame.get(1, 1), Some('#'));
+        assert_eq!(frame.get(0, 2), Some(' '));
+        assert_eq!(frame.get(2, 0), None);
+    }
+
+    #[test]
+    fn creates_frames_from_grids() {
+        let grid = vec![vec![true, false], vec![false, true]];
+        let frame = Frame::from_grid(&grid, |c| if *c { '#' } else { '.' });
+        assert_eq!(frame.render(), "#.\n.#\n");
+    }
+
+    #[test]
+    fn renders_colors() {
+        let mut frame = Frame::from_text("ab");
+        frame.set_color(0, 1, Color::rgb(1, 2, 3));
+        frame.set_color(5, 5, Color::rgb(1, 2, 3));
+        assert_eq!(frame.render(), "a\x1b[38;2;1;2;3mb\x1b[0m\n");
+        assert_eq!(frame.color(0, 1), Color::rgb(1, 2, 3));
+    }
+
+    #[test]
+    fn renders_casts() {
+        let frames = [
+            Frame::from_text("#.").with_caption("step 1"),
+            Frame::from_text(".#").with_caption("step 2"),
+        ];
+        let cast = to_cast(&frames, 2);
+        let lines: Vec<&str> = cast.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains(r#""version":2"#));
+        assert!(lines[0].contains(r#""width":2"#));
+        assert!(lines[0].contains(r#""height":2"#));
+        assert!(lines[2].starts_with("[0.5,\"o\","));
+        assert!(lines[2].contains(r".#\r\nstep 2"));
+    }
+
+    #[test]
+    fn parses_options() {
+        let args: Vec<String> = ["01", "--fps", "30", "--export", "gif"]
+            .iter()
+            .map(|s| (*s).to_string())
+            .collect();
+        let options = Options::from_args(&args);
+        assert_eq!(options.fps, 30);
+        assert_eq!(options.export.as_deref(), Some("gif"));
+        assert_eq!(Options::from_args(&[]).fps, 10);
+    }
+}